@@ -0,0 +1,30 @@
+Feature: Canvas
+
+  Scenario: Creating a canvas
+  Given c <- canvas(10, 20)
+  Then c.width == 10
+  And c.height == 20
+  And every pixel of c is color 0.0, 0.0, 0.0
+
+  Scenario: Writing pixels to a canvas
+  Given c <- canvas(10, 20)
+  And red <- color 1.0, 0.0, 0.0
+  When write_pixel(c, 2, 3, red)
+  Then pixel_at(c, 2, 3) == red
+
+  Scenario: Filling a canvas
+  Given c <- canvas(5, 3)
+  And orange <- color 1.0, 0.5, 0.0
+  When fill(c, orange)
+  Then every pixel of c is color 1.0, 0.5, 0.0
+
+  Scenario: Reading outside the canvas is an error
+  Given c <- canvas(10, 20)
+  Then pixel_at(c, 10, 0) is out of bounds
+  And pixel_at(c, 0, 20) is out of bounds
+
+  Scenario: Writing outside the canvas is an error
+  Given c <- canvas(10, 20)
+  And red <- color 1.0, 0.0, 0.0
+  Then write_pixel(c, 10, 20, red) is out of bounds
+  And every pixel of c is color 0.0, 0.0, 0.0
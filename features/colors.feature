@@ -0,0 +1,38 @@
+Feature: Colors
+
+  Scenario: Colors are (red, green, blue) tuples
+  Given c <- color -0.5, 0.4, 1.7
+  Then c.red == -0.5
+  And c.green == 0.4
+  And c.blue == 1.7
+
+  Scenario: Adding colors
+  Given c1 <- color 0.9, 0.6, 0.75
+  And c2 <- color 0.7, 0.1, 0.25
+  Then c1 + c2 == color 1.6, 0.7, 1.0
+
+  Scenario: Subtracting colors
+  Given c1 <- color 0.9, 0.6, 0.75
+  And c2 <- color 0.7, 0.1, 0.25
+  Then c1 - c2 == color 0.2, 0.5, 0.5
+
+  Scenario: Multiplying a color by a scalar
+  Given c <- color 0.2, 0.3, 0.4
+  Then c * 2.0 == color 0.4, 0.6, 0.8
+
+  Scenario: Multiplying colors
+  Given c1 <- color 1.0, 0.2, 0.4
+  And c2 <- color 0.9, 1.0, 0.1
+  Then c1 * c2 == color 0.9, 0.2, 0.04
+
+  Scenario: Converting a color to 8-bit channels clamps it
+  Given c <- color -0.5, 0.5, 1.7
+  Then rgb8 c == 0, 128, 255
+
+  Scenario: Converting a color to 16-bit channels clamps it
+  Given c <- color 1.5, 0.0, -0.2
+  Then rgb16 c == 65535, 0, 0
+
+  Scenario: Converting an in-range color to 8-bit channels
+  Given c <- color 0.0, 0.2, 1.0
+  Then rgb8 c == 0, 51, 255
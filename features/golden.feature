@@ -0,0 +1,23 @@
+Feature: Golden images
+
+  Scenario: A gradient renders like its golden image
+  Given the scene "gradient" rendered at 64x48
+  Then the image matches "golden/gradient.ppm" within 0.01
+
+  Scenario: A clock face renders like its golden image
+  Given the scene "clock" rendered at 64x48
+  Then the image matches "golden/clock.ppm" within 0.01
+
+  Scenario: Blessing writes the golden image in the format of its extension
+  Given the scene "gradient" rendered at 8x6
+  When the image is blessed as "../target/golden-blessed/gradient.pfm"
+  Then the file "../target/golden-blessed/gradient.pfm" starts with "PF"
+  And the image matches "../target/golden-blessed/gradient.pfm" within 0.000001
+
+  Scenario: A mismatch leaves the actual and diff images behind
+  Given the scene "gradient" rendered at 64x48
+  When the image is blessed as "../target/golden-blessed/expected.ppm"
+  Then the file "../target/golden-blessed/expected.ppm" starts with "P3"
+  Given the scene "clock" rendered at 64x48
+  Then the image does not match "../target/golden-blessed/expected.ppm" within 0.01
+  And the actual and diff images of "../target/golden-blessed/expected.ppm" are written
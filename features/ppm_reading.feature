@@ -0,0 +1,107 @@
+Feature: PPM import
+
+  Scenario: Reading a file with the wrong magic number
+  Given ppm <- a file containing:
+    """
+    P32
+    1 1
+    255
+    0 0 0
+    """
+  Then canvas_from_ppm(ppm) fails with a bad magic number
+
+  Scenario: Reading a PPM returns a canvas of the right size
+  Given ppm <- a file containing:
+    """
+    P3
+    10 2
+    255
+    0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+    0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+    0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+    0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+    """
+  When c <- canvas_from_ppm(ppm)
+  Then c.width == 10
+  And c.height == 2
+
+  Scenario: Reading pixel data from a PPM file
+  Given ppm <- a file containing:
+    """
+    P3
+    4 3
+    255
+    255 127 0  0 127 255  127 255 0  255 255 255
+    0 0 0  255 0 0  0 255 0  0 0 255
+    255 255 0  0 255 255  255 0 255  127 127 127
+    """
+  When c <- canvas_from_ppm(ppm)
+  Then pixel_at(c, 0, 0) == color 1.0, 0.49804, 0.0
+  And pixel_at(c, 1, 0) == color 0.0, 0.49804, 1.0
+  And pixel_at(c, 3, 0) == color 1.0, 1.0, 1.0
+  And pixel_at(c, 1, 1) == color 1.0, 0.0, 0.0
+  And pixel_at(c, 0, 2) == color 1.0, 1.0, 0.0
+  And pixel_at(c, 3, 2) == color 0.49804, 0.49804, 0.49804
+
+  Scenario: PPM parsing ignores comment lines
+  Given ppm <- a file containing:
+    """
+    P3
+    # this is a comment
+    2 1
+    # this, too
+    255
+    # another comment
+    255 255 255
+    # oh, no, comments in the pixel data!
+    255 0 255
+    """
+  When c <- canvas_from_ppm(ppm)
+  Then pixel_at(c, 0, 0) == color 1.0, 1.0, 1.0
+  And pixel_at(c, 1, 0) == color 1.0, 0.0, 1.0
+
+  Scenario: PPM parsing allows an RGB triple to span lines
+  Given ppm <- a file containing:
+    """
+    P3
+    1 1
+    255
+    51
+    153
+
+    204
+    """
+  When c <- canvas_from_ppm(ppm)
+  Then pixel_at(c, 0, 0) == color 0.2, 0.6, 0.8
+
+  Scenario: PPM parsing respects the scale setting
+  Given ppm <- a file containing:
+    """
+    P3
+    2 2
+    100
+    100 100 100  50 50 50
+    75 50 25  0 0 0
+    """
+  When c <- canvas_from_ppm(ppm)
+  Then pixel_at(c, 0, 1) == color 0.75, 0.5, 0.25
+
+  Scenario: Reading a PPM with missing samples
+  Given ppm <- a file containing:
+    """
+    P3
+    2 1
+    255
+    255 255 255
+    """
+  Then canvas_from_ppm(ppm) fails with truncated data
+
+  Scenario: Reading a PPM with a sample above the max value
+  Given ppm <- a file containing:
+    """
+    P3
+    1 1
+    15
+    0 16 0
+    """
+  Then canvas_from_ppm(ppm) fails with a value out of range
@@ -0,0 +1,268 @@
+use std::cmp::Ordering;
+
+use crate::color::Color;
+use crate::matrix::Matrix;
+use crate::quaternion::Quaternion;
+use crate::ray::{Point, Tuple, Vector};
+use crate::scalar::Scalar;
+
+/// Approximate equality, component by component.
+///
+/// Values are equal when every pair of components is within an absolute `epsilon`,
+/// or, for `relative_eq` and `ulps_eq`, close relative to their magnitude.
+/// `approx_eq` uses the scalar's own `EPSILON`.
+pub trait ApproxEq {
+    type Epsilon: Scalar;
+
+    // Values compared pairwise, always in the same order
+    fn components(&self) -> Vec<Self::Epsilon>;
+
+    fn max_abs_diff(&self, other: &Self) -> Self::Epsilon {
+        fold_components(self, other, |a, b| (a - b).abs())
+    }
+    fn max_relative_diff(&self, other: &Self) -> Self::Epsilon {
+        fold_components(self, other, |a, b| {
+            let largest = if a.abs() > b.abs() { a.abs() } else { b.abs() };
+            if largest == Self::Epsilon::ZERO {
+                Self::Epsilon::ZERO
+            } else {
+                (a - b).abs() / largest
+            }
+        })
+    }
+    fn max_ulps_diff(&self, other: &Self) -> u64 {
+        let (a, b) = (self.components(), other.components());
+        if a.len() != b.len() {
+            return u64::MAX;
+        }
+        a.iter().zip(b.iter()).map(|(x, y)| x.ulps_diff(*y)).max().unwrap_or(0)
+    }
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.max_abs_diff(other) <= epsilon
+    }
+    // `epsilon` still applies near zero, where relative differences blow up
+    fn relative_eq(&self, other: &Self, epsilon: Self::Epsilon, max_relative: Self::Epsilon) -> bool {
+        self.abs_diff_eq(other, epsilon) || self.max_relative_diff(other) <= max_relative
+    }
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u64) -> bool {
+        self.abs_diff_eq(other, epsilon) || self.max_ulps_diff(other) <= max_ulps
+    }
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.abs_diff_eq(other, Self::Epsilon::EPSILON)
+    }
+}
+
+// Largest `diff` over matching components, infinite when the shapes differ
+fn fold_components<A: ApproxEq + ?Sized>(a: &A, b: &A, diff: impl Fn(A::Epsilon, A::Epsilon) -> A::Epsilon) -> A::Epsilon {
+    let (a, b) = (a.components(), b.components());
+    if a.len() != b.len() {
+        return A::Epsilon::from_f64(f64::INFINITY);
+    }
+    let mut max = A::Epsilon::ZERO;
+    for (x, y) in a.iter().zip(b.iter()) {
+        let d = diff(*x, *y);
+        match d.partial_cmp(&max) {
+            Some(Ordering::Greater) => max = d,
+            // NaN never compares equal
+            None => return d,
+            _ => {}
+        }
+    }
+    max
+}
+
+// Used by the assertion macros to name the default tolerance of a value
+pub fn default_epsilon<A: ApproxEq + ?Sized>(_value: &A) -> A::Epsilon {
+    A::Epsilon::EPSILON
+}
+
+impl ApproxEq for f32 {
+    type Epsilon = f32;
+    fn components(&self) -> Vec<f32> {
+        vec![*self]
+    }
+}
+
+impl ApproxEq for f64 {
+    type Epsilon = f64;
+    fn components(&self) -> Vec<f64> {
+        vec![*self]
+    }
+}
+
+impl<T: Scalar> ApproxEq for Tuple<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        vec![self.x(), self.y(), self.z(), self.w()]
+    }
+}
+
+impl<T: Scalar> ApproxEq for Point<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        vec![self.x(), self.y(), self.z()]
+    }
+}
+
+impl<T: Scalar> ApproxEq for Vector<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        vec![self.x(), self.y(), self.z()]
+    }
+}
+
+impl<T: Scalar> ApproxEq for Color<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        vec![self.red(), self.green(), self.blue()]
+    }
+}
+
+impl<T: Scalar> ApproxEq for Quaternion<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        vec![self.w(), self.x(), self.y(), self.z()]
+    }
+}
+
+// Matrices of different sizes never compare equal, their component counts differ
+impl<T: Scalar> ApproxEq for Matrix<T> {
+    type Epsilon = T;
+    fn components(&self) -> Vec<T> {
+        let size = self.size();
+        (0..size * size).map(|i| self.at(i / size, i % size)).collect()
+    }
+}
+
+/// Asserts that two values are within an absolute epsilon of each other,
+/// the scalar's `EPSILON` unless `epsilon = ...` is given.
+///
+/// ```
+/// use ray_tracer::assert_approx_eq;
+/// use ray_tracer::ray::Tuple;
+/// assert_approx_eq!(Tuple::vector3(1.0, 2.0, 3.0).normalize().magnitude(), 1.0);
+/// assert_approx_eq!(0.26726, Tuple::vector3(1.0, 2.0, 3.0).normalize().x(), epsilon = 0.00001);
+/// ```
+#[macro_export]
+macro_rules! assert_approx_eq {
+    (@check $left:ident, $right:ident, $ok:expr, $tolerance:expr) => {
+        if !$ok {
+            panic!(
+                "assertion `left ≈ right` failed\n      left: {:?}\n     right: {:?}\n      diff: {:?}\n tolerance: {}",
+                $left,
+                $right,
+                $crate::approx::ApproxEq::max_abs_diff($left, $right),
+                $tolerance
+            );
+        }
+    };
+    ($left:expr, $right:expr, epsilon = $epsilon:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let epsilon = $epsilon;
+                $crate::assert_approx_eq!(@check left, right, $crate::approx::ApproxEq::abs_diff_eq(left, right, epsilon), format!("epsilon = {:?}", epsilon))
+            }
+        }
+    };
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let epsilon = $crate::approx::default_epsilon(left);
+                $crate::assert_approx_eq!(@check left, right, $crate::approx::ApproxEq::abs_diff_eq(left, right, epsilon), format!("epsilon = {:?}", epsilon))
+            }
+        }
+    };
+}
+
+/// Asserts that two values are within `max_relative` of each other relative to their magnitude.
+#[macro_export]
+macro_rules! assert_relative_eq {
+    ($left:expr, $right:expr, max_relative = $max_relative:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let epsilon = $crate::approx::default_epsilon(left);
+                let max_relative = $max_relative;
+                $crate::assert_approx_eq!(
+                    @check left,
+                    right,
+                    $crate::approx::ApproxEq::relative_eq(left, right, epsilon, max_relative),
+                    format!("max_relative = {:?}, relative diff = {:?}", max_relative, $crate::approx::ApproxEq::max_relative_diff(left, right))
+                )
+            }
+        }
+    };
+}
+
+/// Asserts that two values are at most `max_ulps` representable values apart.
+#[macro_export]
+macro_rules! assert_ulps_eq {
+    ($left:expr, $right:expr, max_ulps = $max_ulps:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let epsilon = $crate::approx::default_epsilon(left);
+                let max_ulps: u64 = $max_ulps;
+                $crate::assert_approx_eq!(
+                    @check left,
+                    right,
+                    $crate::approx::ApproxEq::ulps_eq(left, right, epsilon, max_ulps),
+                    format!("max_ulps = {}, ulps diff = {}", max_ulps, $crate::approx::ApproxEq::max_ulps_diff(left, right))
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod approx_tests {
+    use super::*;
+
+    #[test]
+    fn approx_default_epsilon_follows_precision() {
+        assert!(1.0_f32.approx_eq(&1.000_000_1));
+        assert!(!1.0_f64.approx_eq(&1.000_000_1));
+        assert!(1.0_f64.abs_diff_eq(&1.000_000_1, 0.00001));
+    }
+
+    #[test]
+    fn approx_relative_scales_with_magnitude() {
+        assert!(!1_000_000.0_f64.abs_diff_eq(&1_000_000.1, 0.00001));
+        assert!(1_000_000.0_f64.relative_eq(&1_000_000.1, 0.00001, 0.000_001));
+        assert!(!1.0_f64.relative_eq(&1.1, 0.00001, 0.000_001));
+    }
+
+    #[test]
+    fn approx_ulps_counts_representable_values() {
+        let next = f32::from_bits(1.0_f32.to_bits() + 2);
+        assert_eq!(1.0_f32.max_ulps_diff(&next), 2);
+        assert_eq!(0.0_f32.max_ulps_diff(&-0.0), 0);
+        assert!(1.0_f32.ulps_eq(&next, 0.0, 2));
+        assert!(!1.0_f32.ulps_eq(&next, 0.0, 1));
+        assert_eq!(f32::NAN.max_ulps_diff(&f32::NAN), u64::MAX);
+        assert!(!f32::NAN.ulps_eq(&f32::NAN, 0.0, 0));
+        assert!(!f64::NAN.ulps_eq(&1.0, 0.0, u64::MAX - 1));
+    }
+
+    #[test]
+    fn approx_compares_every_component() {
+        let a = Tuple::<f32>(1.0, 2.0, 3.0, 4.0);
+        assert!(a.approx_eq(&Tuple(1.0, 2.0, 3.0, 4.000_001)));
+        assert!(!a.approx_eq(&Tuple(1.0, 2.0, 3.0, 4.1)));
+        assert_eq!(a.max_abs_diff(&Tuple(1.5, 2.0, 2.0, 4.0)), 1.0);
+        assert!(!Matrix::<f64>::identity().approx_eq(&Matrix::identity_of_size(3)));
+        assert!(!f64::NAN.approx_eq(&f64::NAN));
+    }
+
+    #[test]
+    fn approx_macros_accept_matching_values() {
+        assert_approx_eq!(Color::new(0.9, 0.2, 0.04), Color::new(0.9, 0.2, 0.4) * Color::new(1.0, 1.0, 0.1));
+        assert_approx_eq!(Point::new(0.0, 0.33333, 0.66667), Point::new(0.0, 1.0 / 3.0, 2.0 / 3.0), epsilon = 0.00001);
+        assert_relative_eq!(1_000_000.0_f64, 1_000_000.1, max_relative = 0.000_001);
+        assert_ulps_eq!(1.0_f32, f32::from_bits(1.0_f32.to_bits() + 1), max_ulps = 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "diff: 0.5")]
+    fn approx_macro_reports_difference() {
+        assert_approx_eq!(Vector::new(1.0, 2.0, 3.0), Vector::new(1.0, 2.5, 3.0));
+    }
+}
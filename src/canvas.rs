@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::color::Color;
+use crate::scalar::{Float, Scalar};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CanvasError {
+    OutOfBounds { x: usize, y: usize, width: usize, height: usize },
+}
+
+impl fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CanvasError::OutOfBounds { x, y, width, height } => write!(f, "pixel ({}, {}) is outside a {}x{} canvas", x, y, width, height),
+        }
+    }
+}
+
+impl Error for CanvasError {}
+
+// Grid of linear colors, row-major with (0, 0) at the top left
+#[derive(Debug, PartialEq, Clone)]
+pub struct Canvas<T = Float> {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color<T>>,
+}
+
+impl<T: Scalar> Canvas<T> {
+    pub fn new(width: usize, height: usize) -> Canvas<T> {
+        Canvas::filled(width, height, Color::black())
+    }
+    pub fn filled(width: usize, height: usize, color: Color<T>) -> Canvas<T> {
+        Canvas { width, height, pixels: vec![color; width * height] }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    fn index(&self, x: usize, y: usize) -> Result<usize, CanvasError> {
+        if x < self.width && y < self.height {
+            Ok(y * self.width + x)
+        } else {
+            Err(CanvasError::OutOfBounds { x, y, width: self.width, height: self.height })
+        }
+    }
+    pub fn pixel_at(&self, x: usize, y: usize) -> Result<Color<T>, CanvasError> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+    pub fn write_pixel(&mut self, x: usize, y: usize, color: Color<T>) -> Result<(), CanvasError> {
+        let i = self.index(x, y)?;
+        self.pixels[i] = color;
+        Ok(())
+    }
+    pub fn fill(&mut self, color: Color<T>) {
+        self.pixels.iter_mut().for_each(|p| *p = color);
+    }
+    // New canvas of the same size with `f` applied to every pixel
+    pub fn map<F: Fn(Color<T>) -> Color<T>>(&self, f: F) -> Canvas<T> {
+        Canvas { width: self.width, height: self.height, pixels: self.pixels.iter().map(|c| f(*c)).collect() }
+    }
+    // Rows from top to bottom, each from left to right
+    pub fn rows(&self) -> impl Iterator<Item = &[Color<T>]> {
+        // chunks panics on 0, an empty canvas has no rows anyway
+        self.pixels.chunks(self.width.max(1))
+    }
+    // Every pixel with its (x, y) position, in row order
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize, &Color<T>)> {
+        let width = self.width;
+        self.pixels.iter().enumerate().map(move |(i, c)| (i % width, i / width, c))
+    }
+    pub fn pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut Color<T>)> {
+        let width = self.width;
+        self.pixels.iter_mut().enumerate().map(move |(i, c)| (i % width, i / width, c))
+    }
+}
+
+#[cfg(test)]
+mod canvas_tests {
+    use super::*;
+
+    #[test]
+    fn canvas_starts_black() {
+        let c: Canvas = Canvas::new(10, 20);
+        assert_eq!((c.width(), c.height()), (10, 20));
+        assert!(c.pixels().all(|(_, _, p)| *p == Color::black()));
+    }
+
+    #[test]
+    fn canvas_rejects_out_of_bounds_access() {
+        let mut c: Canvas<f64> = Canvas::new(10, 20);
+        let red = Color::new(1.0, 0.0, 0.0);
+        assert_eq!(c.write_pixel(2, 3, red), Ok(()));
+        assert_eq!(c.pixel_at(2, 3), Ok(red));
+        assert_eq!(c.write_pixel(10, 3, red), Err(CanvasError::OutOfBounds { x: 10, y: 3, width: 10, height: 20 }));
+        assert_eq!(c.pixel_at(0, 20).unwrap_err().to_string(), "pixel (0, 20) is outside a 10x20 canvas");
+    }
+
+    #[test]
+    fn canvas_iterates_in_row_order() {
+        let mut c: Canvas<f64> = Canvas::filled(3, 2, Color::white());
+        let (red, blue) = (Color::new(1.0, 0.0, 0.0), Color::new(0.0, 0.0, 1.0));
+        c.write_pixel(2, 0, red).unwrap();
+        c.write_pixel(0, 1, blue).unwrap();
+        let rows: Vec<&[Color<f64>]> = c.rows().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][2], red);
+        assert_eq!(rows[1][0], blue);
+        assert_eq!(c.pixels().nth(3), Some((0, 1, &blue)));
+        for (x, _, p) in c.pixels_mut() {
+            *p = Color::new(x as f64, 0.0, 0.0);
+        }
+        assert_eq!(c.pixel_at(2, 1), Ok(Color::new(2.0, 0.0, 0.0)));
+        c.fill(Color::black());
+        assert_eq!(c, Canvas::new(3, 2));
+    }
+}
@@ -0,0 +1,166 @@
+use std::ops::{Add, Mul, Sub};
+
+use crate::scalar::{Float, Scalar};
+
+// Linear RGB, channels are nominally in 0..1 but may go beyond while shading
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub struct Color<T = Float>(pub T, pub T, pub T);
+
+impl<T: Scalar> Color<T> {
+    pub fn new(red: T, green: T, blue: T) -> Color<T> {
+        Color(red, green, blue)
+    }
+    pub fn black() -> Color<T> {
+        Color(T::ZERO, T::ZERO, T::ZERO)
+    }
+    pub fn white() -> Color<T> {
+        Color(T::ONE, T::ONE, T::ONE)
+    }
+    pub fn red(&self) -> T {
+        self.0
+    }
+    pub fn green(&self) -> T {
+        self.1
+    }
+    pub fn blue(&self) -> T {
+        self.2
+    }
+    pub fn add(&self, other: &Color<T>) -> Color<T> {
+        Color(self.red() + other.red(), self.green() + other.green(), self.blue() + other.blue())
+    }
+    pub fn sub(&self, other: &Color<T>) -> Color<T> {
+        Color(self.red() - other.red(), self.green() - other.green(), self.blue() - other.blue())
+    }
+    pub fn scale(&self, scalar: T) -> Color<T> {
+        Color(self.red() * scalar, self.green() * scalar, self.blue() * scalar)
+    }
+    // Component-wise product, used to blend a light with a surface color
+    pub fn hadamard(&self, other: &Color<T>) -> Color<T> {
+        Color(self.red() * other.red(), self.green() * other.green(), self.blue() * other.blue())
+    }
+    // Rec. 709 relative luminance, gray colors keep their value
+    pub fn luminance(&self) -> T {
+        self.red() * T::from_f64(0.2126) + self.green() * T::from_f64(0.7152) + self.blue() * T::from_f64(0.0722)
+    }
+    pub fn clamp(&self) -> Color<T> {
+        Color(clamp_channel(self.red()), clamp_channel(self.green()), clamp_channel(self.blue()))
+    }
+    pub fn to_rgb8(&self) -> [u8; 3] {
+        [channel_to_u8(self.red()), channel_to_u8(self.green()), channel_to_u8(self.blue())]
+    }
+    pub fn to_rgb16(&self) -> [u16; 3] {
+        [channel_to_u16(self.red()), channel_to_u16(self.green()), channel_to_u16(self.blue())]
+    }
+    pub fn from_rgb8(rgb: [u8; 3]) -> Color<T> {
+        let max = T::from_f64(u8::MAX as f64);
+        Color(T::from_f64(rgb[0] as f64) / max, T::from_f64(rgb[1] as f64) / max, T::from_f64(rgb[2] as f64) / max)
+    }
+    pub fn from_rgb16(rgb: [u16; 3]) -> Color<T> {
+        let max = T::from_f64(u16::MAX as f64);
+        Color(T::from_f64(rgb[0] as f64) / max, T::from_f64(rgb[1] as f64) / max, T::from_f64(rgb[2] as f64) / max)
+    }
+}
+
+fn clamp_channel<T: Scalar>(c: T) -> T {
+    if c < T::ZERO {
+        T::ZERO
+    } else if c > T::ONE {
+        T::ONE
+    } else {
+        c
+    }
+}
+
+// Scales a channel clamped to 0..1 to 0..=max, rounding to the nearest level
+pub fn channel_to_level<T: Scalar>(c: T, max: u16) -> u16 {
+    (clamp_channel(c).to_f64() * max as f64).round() as u16
+}
+
+pub fn channel_to_u8<T: Scalar>(c: T) -> u8 {
+    channel_to_level(c, u8::MAX as u16) as u8
+}
+
+pub fn channel_to_u16<T: Scalar>(c: T) -> u16 {
+    channel_to_level(c, u16::MAX)
+}
+
+impl<T: Scalar> Add for Color<T> {
+    type Output = Color<T>;
+    fn add(self, other: Color<T>) -> Color<T> {
+        Color::add(&self, &other)
+    }
+}
+
+impl<T: Scalar> Add<&Color<T>> for Color<T> {
+    type Output = Color<T>;
+    fn add(self, other: &Color<T>) -> Color<T> {
+        Color::add(&self, other)
+    }
+}
+
+impl<T: Scalar> Sub for Color<T> {
+    type Output = Color<T>;
+    fn sub(self, other: Color<T>) -> Color<T> {
+        Color::sub(&self, &other)
+    }
+}
+
+impl<T: Scalar> Sub<&Color<T>> for Color<T> {
+    type Output = Color<T>;
+    fn sub(self, other: &Color<T>) -> Color<T> {
+        Color::sub(&self, other)
+    }
+}
+
+impl<T: Scalar> Mul<T> for Color<T> {
+    type Output = Color<T>;
+    fn mul(self, scalar: T) -> Color<T> {
+        self.scale(scalar)
+    }
+}
+
+impl<T: Scalar> Mul for Color<T> {
+    type Output = Color<T>;
+    fn mul(self, other: Color<T>) -> Color<T> {
+        self.hadamard(&other)
+    }
+}
+
+impl<T: Scalar> Mul<&Color<T>> for Color<T> {
+    type Output = Color<T>;
+    fn mul(self, other: &Color<T>) -> Color<T> {
+        self.hadamard(other)
+    }
+}
+
+#[cfg(test)]
+mod color_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn color_clamps_before_quantizing() {
+        let c = Color::new(-0.5, 0.5, 1.7);
+        assert_eq!(c.clamp(), Color::new(0.0, 0.5, 1.0));
+        assert_eq!(c.to_rgb8(), [0, 128, 255]);
+        assert_eq!(c.to_rgb16(), [0, 32768, 65535]);
+    }
+
+    #[test]
+    fn color_round_trips_through_8_bits() {
+        let c: Color<f32> = Color::from_rgb8([0, 51, 255]);
+        assert_approx_eq!(c, Color::new(0.0, 0.2, 1.0));
+        assert_eq!(c.to_rgb8(), [0, 51, 255]);
+        assert_eq!(Color::<f64>::from_rgb16([0, 1000, 65535]).to_rgb16(), [0, 1000, 65535]);
+    }
+
+    #[test]
+    fn color_operators_match_methods() {
+        let a = Color::new(1.0, 0.2, 0.4);
+        let b = Color::new(0.9, 1.0, 0.1);
+        assert_eq!(a * b, a.hadamard(&b));
+        assert_eq!(a + b, a.add(&b));
+        assert_eq!(a - b, a.sub(&b));
+        assert_eq!(a * 2.0, Color::new(2.0, 0.4, 0.8));
+    }
+}
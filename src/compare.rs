@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::scalar::Scalar;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompareError {
+    SizeMismatch { expected: (usize, usize), actual: (usize, usize) },
+    // Negative or not a finite number, no pixel could be measured against it
+    InvalidTolerance(f64),
+}
+
+impl fmt::Display for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompareError::SizeMismatch { expected, actual } => write!(f, "expected a {}x{} image, got {}x{}", expected.0, expected.1, actual.0, actual.1),
+            CompareError::InvalidTolerance(tolerance) => write!(f, "tolerance {} must be a finite number of at least 0", tolerance),
+        }
+    }
+}
+
+impl Error for CompareError {}
+
+// Differences between an expected and an actual canvas
+#[derive(Debug, Clone)]
+pub struct Comparison<T: Scalar> {
+    pub tolerance: T,
+    // Largest channel difference and the pixel it was found at
+    pub max_error: T,
+    pub max_error_at: (usize, usize),
+    // Root mean square of every channel difference
+    pub rmse: T,
+    // Peak signal to noise ratio in dB for a 0..1 signal, infinite for identical images
+    pub psnr: f64,
+    // Pixels with a channel further than `tolerance` from the expected one
+    pub mismatched: usize,
+    // Dimmed expected image with mismatches painted from yellow to red as the error grows
+    pub diff: Canvas<T>,
+}
+
+impl<T: Scalar> Comparison<T> {
+    pub fn matches(&self) -> bool {
+        self.mismatched == 0
+    }
+}
+
+impl<T: Scalar> fmt::Display for Comparison<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} pixels above {}, max error {} at ({}, {}), RMSE {}, PSNR {:.2} dB",
+            self.mismatched, self.tolerance, self.max_error, self.max_error_at.0, self.max_error_at.1, self.rmse, self.psnr
+        )
+    }
+}
+
+// Largest channel difference, infinite when one is NaN or infinite so that it is never within tolerance
+fn pixel_error<T: Scalar>(a: &Color<T>, b: &Color<T>) -> T {
+    let d = *a - *b;
+    [d.red().abs(), d.green().abs(), d.blue().abs()].into_iter().fold(T::ZERO, |m, c| {
+        if !c.to_f64().is_finite() {
+            T::from_f64(f64::INFINITY)
+        } else if c > m {
+            c
+        } else {
+            m
+        }
+    })
+}
+
+pub fn compare<T: Scalar>(expected: &Canvas<T>, actual: &Canvas<T>, tolerance: T) -> Result<Comparison<T>, CompareError> {
+    if !tolerance.to_f64().is_finite() || tolerance < T::ZERO {
+        return Err(CompareError::InvalidTolerance(tolerance.to_f64()));
+    }
+    let size = |c: &Canvas<T>| (c.width(), c.height());
+    if size(expected) != size(actual) {
+        return Err(CompareError::SizeMismatch { expected: size(expected), actual: size(actual) });
+    }
+    let mut max_error = T::ZERO;
+    let mut max_error_at = (0, 0);
+    let mut squares = 0.0;
+    let mut mismatched = 0;
+    let errors: Vec<T> = expected
+        .pixels()
+        .zip(actual.pixels())
+        .map(|((x, y, e), (_, _, a))| {
+            let d = *e - *a;
+            squares += [d.red(), d.green(), d.blue()].iter().map(|c| if c.to_f64().is_finite() { c.to_f64() * c.to_f64() } else { f64::INFINITY }).sum::<f64>();
+            let error = pixel_error(e, a);
+            if error > tolerance {
+                mismatched += 1;
+            }
+            if error > max_error {
+                max_error = error;
+                max_error_at = (x, y);
+            }
+            error
+        })
+        .collect();
+    let samples = (expected.width() * expected.height() * 3).max(1) as f64;
+    let rmse = (squares / samples).sqrt();
+    let psnr = if rmse == 0.0 { f64::INFINITY } else { -20.0 * rmse.log10() };
+
+    let mut diff = expected.map(|c| Color::white().scale(c.luminance() * T::from_f64(0.25)));
+    for ((_, _, pixel), error) in diff.pixels_mut().zip(errors) {
+        if error > tolerance {
+            // Non-finite errors are the reddest, finite ones then all look small
+            let heat = if error.to_f64().is_finite() { error / max_error } else { T::ONE };
+            *pixel = Color::new(T::ONE, T::ONE - heat, T::ZERO);
+        }
+    }
+    Ok(Comparison { tolerance, max_error, max_error_at, rmse: T::from_f64(rmse), psnr, mismatched, diff })
+}
+
+#[cfg(test)]
+mod compare_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn compare_identical_canvases_match() {
+        let c: Canvas<f64> = Canvas::filled(4, 3, Color::new(0.2, 0.4, 0.6));
+        let result = compare(&c, &c, 0.0).unwrap();
+        assert!(result.matches());
+        assert_eq!((result.max_error, result.rmse, result.psnr), (0.0, 0.0, f64::INFINITY));
+        assert!(result.diff.pixels().all(|(_, _, p)| p.red() == p.green() && p.red() < 0.25));
+    }
+
+    #[test]
+    fn compare_locates_and_measures_errors() {
+        let expected: Canvas<f64> = Canvas::new(2, 2);
+        let mut actual = expected.clone();
+        actual.write_pixel(1, 0, Color::new(0.0, 0.005, 0.0)).unwrap();
+        actual.write_pixel(0, 1, Color::new(0.0, 0.0, 0.5)).unwrap();
+        let result = compare(&expected, &actual, 0.01).unwrap();
+        assert!(!result.matches());
+        assert_eq!(result.mismatched, 1);
+        assert_eq!((result.max_error, result.max_error_at), (0.5, (0, 1)));
+        assert_approx_eq!(result.rmse, ((0.25 + 0.000025) / 12.0_f64).sqrt());
+        assert_approx_eq!(result.psnr, -20.0 * result.rmse.log10());
+        assert_eq!(result.diff.pixel_at(0, 1), Ok(Color::new(1.0, 0.0, 0.0)));
+        assert_eq!(result.diff.pixel_at(1, 0), Ok(Color::black()));
+        assert!(compare(&expected, &actual, 0.5).unwrap().matches());
+    }
+
+    #[test]
+    fn compare_rejects_different_sizes() {
+        let err = compare::<f32>(&Canvas::new(2, 2), &Canvas::new(2, 3), 0.0).unwrap_err();
+        assert_eq!(err, CompareError::SizeMismatch { expected: (2, 2), actual: (2, 3) });
+        assert_eq!(err.to_string(), "expected a 2x2 image, got 2x3");
+        let c = Canvas::<f32>::new(1, 1);
+        for tolerance in [f32::NAN, f32::INFINITY, -0.1] {
+            assert!(matches!(compare(&c, &c, tolerance), Err(CompareError::InvalidTolerance(_))));
+        }
+        assert_eq!(compare(&c, &c, -1.0).unwrap_err().to_string(), "tolerance -1 must be a finite number of at least 0");
+    }
+
+    #[test]
+    fn compare_counts_nan_pixels_as_mismatches() {
+        let expected: Canvas<f32> = Canvas::new(2, 1);
+        let mut actual = expected.clone();
+        actual.write_pixel(0, 0, Color::new(0.0, f32::NAN, 0.0)).unwrap();
+        actual.write_pixel(1, 0, Color::new(0.5, 0.0, 0.0)).unwrap();
+        let result = compare(&expected, &actual, 0.01).unwrap();
+        assert!(!result.matches());
+        assert_eq!(result.mismatched, 2);
+        assert_eq!((result.max_error, result.max_error_at), (f32::INFINITY, (0, 0)));
+        assert_eq!((result.rmse, result.psnr), (f32::INFINITY, f64::NEG_INFINITY));
+        assert_eq!(result.diff.pixel_at(0, 0), Ok(Color::new(1.0, 0.0, 0.0)));
+        assert_eq!(result.diff.pixel_at(1, 0), Ok(Color::new(1.0, 1.0, 0.0)));
+    }
+}
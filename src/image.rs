@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::canvas::Canvas;
+use crate::pfm::{PfmChannels, PfmError};
+use crate::png::PngOptions;
+use crate::ppm::PpmError;
+use crate::scalar::Scalar;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BitDepth {
+    Eight,
+    Sixteen,
+}
+
+impl BitDepth {
+    pub fn max_value(&self) -> u16 {
+        match self {
+            BitDepth::Eight => u8::MAX as u16,
+            BitDepth::Sixteen => u16::MAX,
+        }
+    }
+}
+
+// File formats a canvas can be saved as
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImageFormat {
+    // ASCII P3, always 8 bits
+    PlainPpm,
+    // Binary P6
+    Ppm(BitDepth),
+    // Binary P5 of the luminance, for single channel passes such as depth
+    Pgm(BitDepth),
+    Png(PngOptions),
+    // 32-bit floats, keeps colors above 1.0
+    Pfm(PfmChannels),
+}
+
+impl ImageFormat {
+    // Default format for a file extension: binary 8-bit for Netpbm, RGB for PFM
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm(BitDepth::Eight)),
+            "pgm" => Some(ImageFormat::Pgm(BitDepth::Eight)),
+            "png" => Some(ImageFormat::Png(PngOptions::default())),
+            "pfm" => Some(ImageFormat::Pfm(PfmChannels::Rgb)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Ppm(PpmError),
+    Pfm(PfmError),
+    // Only PPM and PFM files can be read back
+    UnsupportedFormat(PathBuf),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Ppm(e) => e.fmt(f),
+            LoadError::Pfm(e) => e.fmt(f),
+            LoadError::UnsupportedFormat(path) => write!(f, "cannot read {}, only .ppm and .pfm images are supported", path.display()),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Ppm(e) => Some(e),
+            LoadError::Pfm(e) => Some(e),
+            LoadError::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+impl<T: Scalar> Canvas<T> {
+    // Picks the reader from the file extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Canvas<T>, LoadError> {
+        let path = path.as_ref();
+        match ImageFormat::from_path(path) {
+            Some(ImageFormat::Ppm(_)) => Canvas::load_ppm(path).map_err(LoadError::Ppm),
+            Some(ImageFormat::Pfm(_)) => Canvas::load_pfm(path).map_err(LoadError::Pfm),
+            _ => Err(LoadError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+    pub fn write_image<W: Write>(&self, format: ImageFormat, out: W) -> io::Result<()> {
+        match format {
+            ImageFormat::PlainPpm => self.write_ppm(out),
+            ImageFormat::Ppm(depth) => self.write_binary_ppm(depth, out),
+            ImageFormat::Pgm(depth) => self.write_pgm(depth, out),
+            ImageFormat::Png(options) => self.write_png(options, out),
+            ImageFormat::Pfm(channels) => self.write_pfm(channels, out),
+        }
+    }
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: ImageFormat) -> io::Result<()> {
+        self.write_image(format, BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod image_tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn image_save_writes_the_selected_format() {
+        let c: Canvas<f64> = Canvas::filled(2, 1, Color::new(1.0, 0.5, 0.0));
+        let path = std::env::temp_dir().join(format!("ray_tracer_image_{}.pgm", std::process::id()));
+        c.save(&path, ImageFormat::Pgm(BitDepth::Eight)).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Luminance of (1, 0.5, 0) is 0.5702
+        assert_eq!(saved, b"P5\n2 1\n255\n\x91\x91");
+    }
+
+    #[test]
+    fn image_format_follows_the_extension() {
+        assert_eq!(ImageFormat::from_path("out/render.PPM"), Some(ImageFormat::Ppm(BitDepth::Eight)));
+        assert_eq!(ImageFormat::from_path("depth.pgm"), Some(ImageFormat::Pgm(BitDepth::Eight)));
+        assert_eq!(ImageFormat::from_path("hdr.pfm"), Some(ImageFormat::Pfm(PfmChannels::Rgb)));
+        assert_eq!(ImageFormat::from_path("render"), None);
+        assert!(matches!(Canvas::<f32>::load("render.png"), Err(LoadError::UnsupportedFormat(_))));
+    }
+}
@@ -0,0 +1,18 @@
+pub mod approx;
+pub mod canvas;
+pub mod color;
+pub mod compare;
+pub mod image;
+pub mod matrix;
+pub mod pfm;
+pub mod png;
+pub mod ppm;
+pub mod projectile;
+pub mod quaternion;
+pub mod ray;
+pub mod scalar;
+pub mod tonemap;
+pub mod transform;
+pub mod zlib;
+
+pub use scalar::{Float, Scalar};
@@ -1,19 +1,123 @@
-mod projectile;
+use std::env;
+use std::io;
+use std::process;
+
+use ray_tracer::canvas::Canvas;
+use ray_tracer::compare;
+use ray_tracer::image::ImageFormat;
+use ray_tracer::projectile::{self, Environment, LaunchOptions, PlotOptions, Projectile};
+use ray_tracer::ray::{Point, Vector};
+use ray_tracer::Float;
 
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("compare") {
+        process::exit(compare_images(&args[1..]));
+    }
+
     println!("Hello, world!");
     println!("Cannon ball xp");
 
-    cannon_ball(0.0, 1.0, 0.0, 1.0, 1.0, 0.0);
-    cannon_ball(0.0, 1.0, 0.0, 5.0, 3.0, 0.0);
+    cannon_ball(0.0, 1.0, 0.0, 1.0, 1.0, 0.0, "cannon_ball_1.png");
+    cannon_ball(0.0, 1.0, 0.0, 5.0, 3.0, 0.0, "cannon_ball_2.png");
 
 }
 
-fn cannon_ball(pos_x: f32, pos_y:f32, pos_z: f32, v_x: f32, v_y: f32, v_z: f32) {
+fn cannon_ball(pos_x: Float, pos_y: Float, pos_z: Float, v_x: Float, v_y: Float, v_z: Float, plot_path: &str) {
     println!("Projectile a cannon ball simulation");
     println!("Start from ({}, {}, {}) with velocity ({}, {}, {})", pos_x, pos_y, pos_z, v_x, v_y, v_z);
-    let pos_by_ticks = projectile::launch(pos_x, pos_y, pos_z, v_x, v_y, v_z);
-    println!("use {} ticks to arrive on floor", pos_by_ticks.len());
-    projectile::trace_trajectory(&pos_by_ticks);
-}
\ No newline at end of file
+    let initial = Projectile::new(Point::new(pos_x, pos_y, pos_z), Vector::new(v_x, v_y, v_z));
+    // The velocity is kept as given so that each launch flies at its own speed
+    let trajectory = projectile::launch_with(&Environment::default(), &initial, &LaunchOptions::default().keep_velocity_magnitude());
+    println!("use {} ticks, stopped as {:?}", trajectory.samples.len(), trajectory.termination);
+    if let Err(e) = trajectory.write_csv(io::stdout().lock()) {
+        eprintln!("cannot write the trajectory: {}", e);
+    }
+    let plot = projectile::plot_trajectory(&trajectory.samples, &PlotOptions::default());
+    let format = ImageFormat::from_path(plot_path).expect("plot path has an image extension");
+    match plot.save(plot_path, format) {
+        Ok(()) => println!("trajectory plotted in {}", plot_path),
+        Err(e) => eprintln!("cannot save {}: {}", plot_path, e),
+    }
+}
+
+const COMPARE_USAGE: &str = "usage: ray_tracer compare <expected> <actual> [--tolerance <t>] [--diff <path>]";
+
+// Exits with 0 when the images match, 1 when a pixel is off by more than the tolerance, 2 on bad input
+fn compare_images(args: &[String]) -> i32 {
+    match run_compare(args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(message) => {
+            eprintln!("{}", message);
+            2
+        }
+    }
+}
+
+fn run_compare(args: &[String]) -> Result<bool, String> {
+    let mut paths = Vec::new();
+    let mut tolerance: Float = 0.01;
+    let mut diff_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tolerance" => {
+                let value = args.next().ok_or(COMPARE_USAGE)?;
+                tolerance = value.parse().ok().filter(|t: &Float| t.is_finite() && *t >= 0.0).ok_or_else(|| format!("invalid tolerance {:?}\n{}", value, COMPARE_USAGE))?;
+            }
+            "--diff" => diff_path = Some(args.next().ok_or(COMPARE_USAGE)?),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 2 {
+        return Err(COMPARE_USAGE.to_string());
+    }
+    let load = |path: &String| Canvas::<Float>::load(path).map_err(|e| format!("{}: {}", path, e));
+    let expected = load(paths[0])?;
+    let actual = load(paths[1])?;
+    let result = compare::compare(&expected, &actual, tolerance).map_err(|e| e.to_string())?;
+    println!("{}", result);
+    if let Some(path) = diff_path {
+        let format = ImageFormat::from_path(path).ok_or_else(|| format!("unknown image format for {}", path))?;
+        result.diff.save(path, format).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(result.matches())
+}
+
+#[cfg(test)]
+mod main_tests {
+    use super::*;
+    use ray_tracer::color::Color;
+    use std::path::PathBuf;
+
+    // A PPM under the temporary directory, named after the test using it
+    fn image(name: &str, color: Color) -> String {
+        let path: PathBuf = env::temp_dir().join(format!("ray_tracer_{}_{}.ppm", process::id(), name));
+        Canvas::filled(2, 2, color).save_ppm(&path).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn main_compare_exit_codes() {
+        let grey = image("exit_grey", Color::new(0.5, 0.5, 0.5));
+        let lighter = image("exit_lighter", Color::new(0.6, 0.5, 0.5));
+        assert_eq!(compare_images(&args(&[&grey, &grey])), 0);
+        assert_eq!(compare_images(&args(&[&grey, &lighter])), 1);
+        assert_eq!(compare_images(&args(&[&grey, &lighter, "--tolerance", "0.2"])), 0);
+        assert_eq!(compare_images(&args(&[&grey])), 2);
+        assert_eq!(compare_images(&args(&[&grey, "missing.ppm"])), 2);
+        assert_eq!(compare_images(&args(&[&grey, &lighter, "--tolerance"])), 2);
+        for tolerance in ["NaN", "inf", "-0.5", "much"] {
+            assert_eq!(compare_images(&args(&[&grey, &lighter, "--tolerance", tolerance])), 2, "{}", tolerance);
+        }
+        for path in [grey, lighter] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::Mul;
+
+use crate::ray::{Point, Tuple, Vector};
+use crate::scalar::{Float, Scalar};
+
+// A square matrix stored row by row
+#[derive(Debug, PartialEq, Clone)]
+pub struct Matrix<T = Float> {
+    size: usize,
+    elts: Vec<T>,
+}
+
+impl<T: Scalar> Matrix<T> {
+    pub fn new(size: usize, elts: Vec<T>) -> Matrix<T> {
+        assert_eq!(elts.len(), size * size, "a {}x{} matrix needs {} elements", size, size, size * size);
+        Matrix { size, elts }
+    }
+    pub fn from_rows<const N: usize>(rows: [[T; N]; N]) -> Matrix<T> {
+        Matrix {
+            size: N,
+            elts: rows.iter().flatten().copied().collect(),
+        }
+    }
+    pub fn zeros(size: usize) -> Matrix<T> {
+        Matrix {
+            size,
+            elts: vec![T::ZERO; size * size],
+        }
+    }
+    pub fn identity() -> Matrix<T> {
+        Matrix::identity_of_size(4)
+    }
+    pub fn identity_of_size(size: usize) -> Matrix<T> {
+        let mut m = Matrix::zeros(size);
+        for i in 0..size {
+            m.set(i, i, T::ONE);
+        }
+        m
+    }
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn at(&self, row: usize, col: usize) -> T {
+        assert!(row < self.size && col < self.size, "({}, {}) is outside a {}x{} matrix", row, col, self.size, self.size);
+        self.elts[row * self.size + col]
+    }
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        assert!(row < self.size && col < self.size, "({}, {}) is outside a {}x{} matrix", row, col, self.size, self.size);
+        self.elts[row * self.size + col] = value;
+    }
+    pub fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.size, other.size, "cannot multiply matrices of different sizes");
+        let mut m = Matrix::zeros(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let mut sum = T::ZERO;
+                for k in 0..self.size {
+                    sum += self.at(row, k) * other.at(k, col);
+                }
+                m.set(row, col, sum);
+            }
+        }
+        m
+    }
+    pub fn mul_tuple(&self, t: &Tuple<T>) -> Tuple<T> {
+        assert_eq!(self.size, 4, "only a 4x4 matrix can multiply a tuple");
+        let row = |r: usize| self.at(r, 0) * t.x() + self.at(r, 1) * t.y() + self.at(r, 2) * t.z() + self.at(r, 3) * t.w();
+        Tuple(row(0), row(1), row(2), row(3))
+    }
+    pub fn mul_point(&self, p: &Point<T>) -> Point<T> {
+        let t = self.mul_tuple(&p.to_tuple());
+        Point(t.x(), t.y(), t.z())
+    }
+    pub fn mul_vector(&self, v: &Vector<T>) -> Vector<T> {
+        let t = self.mul_tuple(&v.to_tuple());
+        Vector(t.x(), t.y(), t.z())
+    }
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut m = Matrix::zeros(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                m.set(col, row, self.at(row, col));
+            }
+        }
+        m
+    }
+    pub fn submatrix(&self, row: usize, col: usize) -> Matrix<T> {
+        let elts = (0..self.size)
+            .filter(|r| *r != row)
+            .flat_map(|r| (0..self.size).filter(|c| *c != col).map(move |c| (r, c)))
+            .map(|(r, c)| self.at(r, c))
+            .collect();
+        Matrix::new(self.size - 1, elts)
+    }
+    pub fn minor(&self, row: usize, col: usize) -> T {
+        self.submatrix(row, col).determinant()
+    }
+    pub fn cofactor(&self, row: usize, col: usize) -> T {
+        let minor = self.minor(row, col);
+        if (row + col).is_multiple_of(2) {
+            minor
+        } else {
+            -minor
+        }
+    }
+    pub fn determinant(&self) -> T {
+        match self.size {
+            0 => T::ONE,
+            1 => self.elts[0],
+            2 => self.at(0, 0) * self.at(1, 1) - self.at(0, 1) * self.at(1, 0),
+            _ => (0..self.size).fold(T::ZERO, |acc, col| acc + self.at(0, col) * self.cofactor(0, col)),
+        }
+    }
+    pub fn is_invertible(&self) -> bool {
+        self.determinant() != T::ZERO
+    }
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        let det = self.determinant();
+        if det == T::ZERO {
+            return None;
+        }
+        let mut m = Matrix::zeros(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                // transposed on the way in
+                m.set(col, row, self.cofactor(row, col) / det);
+            }
+        }
+        Some(m)
+    }
+}
+
+impl<T: Scalar> Mul for Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, other: Matrix<T>) -> Matrix<T> {
+        Matrix::mul(&self, &other)
+    }
+}
+
+impl<T: Scalar> Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        Matrix::mul(self, other)
+    }
+}
+
+impl<T: Scalar> Mul<Tuple<T>> for Matrix<T> {
+    type Output = Tuple<T>;
+    fn mul(self, t: Tuple<T>) -> Tuple<T> {
+        self.mul_tuple(&t)
+    }
+}
+
+impl<T: Scalar> Mul<Tuple<T>> for &Matrix<T> {
+    type Output = Tuple<T>;
+    fn mul(self, t: Tuple<T>) -> Tuple<T> {
+        self.mul_tuple(&t)
+    }
+}
+
+impl<T: Scalar> Mul<Point<T>> for Matrix<T> {
+    type Output = Point<T>;
+    fn mul(self, p: Point<T>) -> Point<T> {
+        self.mul_point(&p)
+    }
+}
+
+impl<T: Scalar> Mul<Point<T>> for &Matrix<T> {
+    type Output = Point<T>;
+    fn mul(self, p: Point<T>) -> Point<T> {
+        self.mul_point(&p)
+    }
+}
+
+impl<T: Scalar> Mul<Vector<T>> for Matrix<T> {
+    type Output = Vector<T>;
+    fn mul(self, v: Vector<T>) -> Vector<T> {
+        self.mul_vector(&v)
+    }
+}
+
+impl<T: Scalar> Mul<Vector<T>> for &Matrix<T> {
+    type Output = Vector<T>;
+    fn mul(self, v: Vector<T>) -> Vector<T> {
+        self.mul_vector(&v)
+    }
+}
+
+#[cfg(test)]
+mod matrix_tests {
+    use super::*;
+
+    #[test]
+    fn matrix_from_rows_is_row_major() {
+        let m = Matrix::from_rows([[1.0, 2.0], [3.0, 4.0]]);
+        assert_eq!(m.size(), 2);
+        assert_eq!(m.at(0, 1), 2.0);
+        assert_eq!(m.at(1, 0), 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn matrix_new_rejects_wrong_element_count() {
+        Matrix::new(3, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn matrix_singular_has_no_inverse() {
+        let m = Matrix::from_rows([[-4.0, 2.0, -2.0, -3.0], [9.0, 6.0, 2.0, 6.0], [0.0, -5.0, 1.0, -5.0], [0.0, 0.0, 0.0, 0.0]]);
+        assert!(!m.is_invertible());
+        assert_eq!(m.inverse(), None);
+    }
+
+    #[test]
+    fn matrix_multiplies_points_and_vectors() {
+        let m = Matrix::from_rows([[1.0, 0.0, 0.0, 5.0], [0.0, 1.0, 0.0, -3.0], [0.0, 0.0, 1.0, 2.0], [0.0, 0.0, 0.0, 1.0]]);
+        assert_eq!(&m * Point::new(-3.0, 4.0, 5.0), Point::new(2.0, 1.0, 7.0));
+        assert_eq!(&m * Vector::new(-3.0, 4.0, 5.0), Vector::new(-3.0, 4.0, 5.0));
+    }
+}
@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::scalar::Scalar;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PfmChannels {
+    // PF, three floats per pixel
+    Rgb,
+    // Pf, the luminance only
+    Gray,
+}
+
+#[derive(Debug)]
+pub enum PfmError {
+    Io(io::Error),
+    // Anything other than PF or Pf
+    BadMagic(String),
+    Truncated,
+    InvalidNumber(String),
+    // Zero or not finite, its sign gives the byte order
+    InvalidScale(String),
+}
+
+impl fmt::Display for PfmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PfmError::Io(e) => write!(f, "cannot read PFM: {}", e),
+            PfmError::BadMagic(magic) => write!(f, "unsupported PFM magic number {:?}, expected PF or Pf", magic),
+            PfmError::Truncated => write!(f, "PFM data is truncated"),
+            PfmError::InvalidNumber(token) => write!(f, "{:?} is not a valid PFM number", token),
+            PfmError::InvalidScale(token) => write!(f, "PFM scale {:?} must be a non-zero number", token),
+        }
+    }
+}
+
+impl Error for PfmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PfmError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PfmError {
+    fn from(e: io::Error) -> PfmError {
+        PfmError::Io(e)
+    }
+}
+
+// Next whitespace separated header field, starting at `pos`
+fn header_token(data: &[u8], pos: &mut usize) -> Result<String, PfmError> {
+    while *pos < data.len() && data[*pos].is_ascii_whitespace() {
+        *pos += 1;
+    }
+    let start = *pos;
+    while *pos < data.len() && !data[*pos].is_ascii_whitespace() {
+        *pos += 1;
+    }
+    if start == *pos {
+        Err(PfmError::Truncated)
+    } else {
+        Ok(String::from_utf8_lossy(&data[start..*pos]).into_owned())
+    }
+}
+
+fn header_number(data: &[u8], pos: &mut usize) -> Result<usize, PfmError> {
+    let token = header_token(data, pos)?;
+    token.parse().map_err(|_| PfmError::InvalidNumber(token))
+}
+
+impl<T: Scalar> Canvas<T> {
+    // Little-endian 32-bit floats, bottom row first as the format requires, values are not clamped
+    pub fn write_pfm<W: Write>(&self, channels: PfmChannels, mut out: W) -> io::Result<()> {
+        let magic = match channels {
+            PfmChannels::Rgb => "PF",
+            PfmChannels::Gray => "Pf",
+        };
+        write!(out, "{}\n{} {}\n-1.0\n", magic, self.width(), self.height())?;
+        let rows: Vec<&[Color<T>]> = self.rows().take(self.height()).collect();
+        for row in rows.iter().rev() {
+            for c in row.iter() {
+                let samples = match channels {
+                    PfmChannels::Rgb => vec![c.red(), c.green(), c.blue()],
+                    PfmChannels::Gray => vec![c.luminance()],
+                };
+                for sample in samples {
+                    out.write_all(&(sample.to_f64() as f32).to_le_bytes())?;
+                }
+            }
+        }
+        out.flush()
+    }
+    pub fn save_pfm<P: AsRef<Path>>(&self, path: P, channels: PfmChannels) -> io::Result<()> {
+        self.write_pfm(channels, BufWriter::new(File::create(path)?))
+    }
+    // Samples are kept as stored, the scale only gives the byte order
+    pub fn from_pfm(data: &[u8]) -> Result<Canvas<T>, PfmError> {
+        let mut pos = 0;
+        let magic = header_token(data, &mut pos)?;
+        let channels = match magic.as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => return Err(PfmError::BadMagic(magic)),
+        };
+        let width = header_number(data, &mut pos)?;
+        let height = header_number(data, &mut pos)?;
+        let scale_token = header_token(data, &mut pos)?;
+        let scale: f32 = scale_token.parse().map_err(|_| PfmError::InvalidScale(scale_token.clone()))?;
+        if scale == 0.0 || !scale.is_finite() {
+            return Err(PfmError::InvalidScale(scale_token));
+        }
+        // A single whitespace byte separates the header from the raster
+        let raster = data.get(pos + 1..).ok_or(PfmError::Truncated)?;
+        let needed = width.checked_mul(height).and_then(|n| n.checked_mul(channels * 4)).ok_or(PfmError::Truncated)?;
+        if raster.len() < needed {
+            return Err(PfmError::Truncated);
+        }
+        let samples: Vec<T> = raster[..needed]
+            .chunks(4)
+            .map(|b| {
+                let bytes = [b[0], b[1], b[2], b[3]];
+                let value = if scale < 0.0 { f32::from_le_bytes(bytes) } else { f32::from_be_bytes(bytes) };
+                T::from_f64(value as f64)
+            })
+            .collect();
+        let mut canvas = Canvas::new(width, height);
+        for (i, pixel) in samples.chunks(channels).enumerate() {
+            let color = if channels == 3 { Color::new(pixel[0], pixel[1], pixel[2]) } else { Color::new(pixel[0], pixel[0], pixel[0]) };
+            // Stored bottom to top
+            canvas.write_pixel(i % width, height - 1 - i / width, color).expect("sample count matches the canvas");
+        }
+        Ok(canvas)
+    }
+    pub fn read_pfm<R: Read>(mut input: R) -> Result<Canvas<T>, PfmError> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+        Canvas::from_pfm(&data)
+    }
+    pub fn load_pfm<P: AsRef<Path>>(path: P) -> Result<Canvas<T>, PfmError> {
+        Canvas::read_pfm(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod pfm_tests {
+    use super::*;
+
+    #[test]
+    fn pfm_keeps_unclamped_radiance_exactly() {
+        let mut c: Canvas<f32> = Canvas::new(3, 2);
+        c.write_pixel(0, 0, Color::new(12.5, -0.25, 1.0e6)).unwrap();
+        c.write_pixel(2, 1, Color::new(0.1, 0.2, 0.3)).unwrap();
+        let mut pfm = Vec::new();
+        c.write_pfm(PfmChannels::Rgb, &mut pfm).unwrap();
+        assert!(pfm.starts_with(b"PF\n3 2\n-1.0\n"));
+        // The bottom row comes first
+        assert_eq!(pfm[pfm.len() - 36..pfm.len() - 32], 12.5_f32.to_le_bytes());
+        assert_eq!(Canvas::<f32>::from_pfm(&pfm).unwrap(), c);
+    }
+
+    #[test]
+    fn pfm_reads_gray_and_big_endian_maps() {
+        let mut pf = b"Pf\n2 1\n1.0\n".to_vec();
+        pf.extend_from_slice(&2.0_f32.to_be_bytes());
+        pf.extend_from_slice(&0.5_f32.to_be_bytes());
+        let c: Canvas<f64> = Canvas::from_pfm(&pf).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Ok(Color::new(2.0, 2.0, 2.0)));
+        assert_eq!(c.pixel_at(1, 0), Ok(Color::new(0.5, 0.5, 0.5)));
+
+        let mut gray = Vec::new();
+        c.write_pfm(PfmChannels::Gray, &mut gray).unwrap();
+        assert_eq!(Canvas::<f64>::from_pfm(&gray).unwrap(), c);
+    }
+
+    #[test]
+    fn pfm_reports_typed_errors() {
+        let read = |data: &[u8]| Canvas::<f32>::from_pfm(data).unwrap_err();
+        assert!(matches!(read(b"P6\n1 1\n-1.0\n"), PfmError::BadMagic(m) if m == "P6"));
+        assert!(matches!(read(b"PF\n1 1\n-1.0\n\0\0\0\0"), PfmError::Truncated));
+        assert!(matches!(read(b"PF\n1 1\n0\n"), PfmError::InvalidScale(_)));
+        assert!(matches!(read(b"PF\n1 one\n-1.0\n"), PfmError::InvalidNumber(t) if t == "one"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::color::channel_to_level;
+use crate::image::BitDepth;
+use crate::scalar::Scalar;
+use crate::zlib::{zlib_compress, Compression};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// CRC-32 as used by PNG chunks and zip
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |c, &b| CRC_TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PngOptions {
+    pub depth: BitDepth,
+    // Adds a fully opaque alpha channel
+    pub alpha: bool,
+    pub compression: Compression,
+}
+
+impl Default for PngOptions {
+    fn default() -> PngOptions {
+        PngOptions { depth: BitDepth::Eight, alpha: false, compression: Compression::Deflate }
+    }
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let mut crc_input = kind.to_vec();
+    crc_input.extend_from_slice(data);
+    out.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+impl<T: Scalar> Canvas<T> {
+    // Non-interlaced RGB or RGBA, every scanline unfiltered
+    pub fn write_png<W: Write>(&self, options: PngOptions, mut out: W) -> io::Result<()> {
+        let max = options.depth.max_value();
+        let (bit_depth, sample_bytes) = match options.depth {
+            BitDepth::Eight => (8u8, 1),
+            BitDepth::Sixteen => (16u8, 2),
+        };
+        let (color_type, channels) = if options.alpha { (6u8, 4) } else { (2u8, 3) };
+
+        // PNG sizes are non-zero 32-bit numbers
+        let dimension = |size: usize| u32::try_from(size).ok().filter(|&s| s > 0);
+        let (width, height) = match (dimension(self.width()), dimension(self.height())) {
+            (Some(width), Some(height)) => (width, height),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("a PNG cannot be {}x{}", self.width(), self.height()))),
+        };
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // Deflate compression, adaptive filtering, no interlace
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+
+        let mut raw = Vec::with_capacity(self.height() * (1 + self.width() * channels * sample_bytes));
+        for row in self.rows().take(self.height()) {
+            raw.push(0);
+            for c in row {
+                let alpha = if options.alpha { Some(max) } else { None };
+                let levels = [c.red(), c.green(), c.blue()].map(|channel| channel_to_level(channel, max));
+                for level in levels.into_iter().chain(alpha) {
+                    match options.depth {
+                        BitDepth::Eight => raw.push(level as u8),
+                        BitDepth::Sixteen => raw.extend_from_slice(&level.to_be_bytes()),
+                    }
+                }
+            }
+        }
+
+        out.write_all(&PNG_SIGNATURE)?;
+        write_chunk(&mut out, b"IHDR", &header)?;
+        write_chunk(&mut out, b"IDAT", &zlib_compress(&raw, options.compression))?;
+        write_chunk(&mut out, b"IEND", &[])?;
+        out.flush()
+    }
+    pub fn save_png<P: AsRef<Path>>(&self, path: P, options: PngOptions) -> io::Result<()> {
+        self.write_png(options, BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod png_tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::zlib::zlib_tests::zlib_decompress;
+
+    // Splits a PNG into (type, data) chunks, checking every CRC
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(png[..8], PNG_SIGNATURE);
+        let mut chunks = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = png[pos + 4..pos + 8].try_into().unwrap();
+            let data = png[pos + 8..pos + 8 + len].to_vec();
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&png[pos + 4..pos + 8 + len]));
+            chunks.push((kind, data));
+            pos += 12 + len;
+        }
+        chunks
+    }
+
+    #[test]
+    fn png_crc32_matches_reference() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn png_stored_output_is_byte_exact() {
+        let c: Canvas<f64> = Canvas::filled(1, 1, Color::new(1.0, 0.0, 0.0));
+        let mut png = Vec::new();
+        c.write_png(PngOptions { compression: Compression::Stored, ..PngOptions::default() }, &mut png).unwrap();
+        let expected: Vec<u8> = [
+            &PNG_SIGNATURE[..],
+            &[0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0, 0x90, 0x77, 0x53, 0xde],
+            &[0, 0, 0, 15, b'I', b'D', b'A', b'T', 0x78, 0x01, 0x01, 0x04, 0x00, 0xfb, 0xff, 0x00, 0xff, 0x00, 0x00, 0x03, 0x01, 0x01, 0x00],
+            &crc32(b"IDAT\x78\x01\x01\x04\x00\xfb\xff\x00\xff\x00\x00\x03\x01\x01\x00").to_be_bytes(),
+            &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82],
+        ]
+        .concat();
+        assert_eq!(png, expected);
+    }
+
+    #[test]
+    fn png_round_trips_pixels_at_every_depth() {
+        let mut c: Canvas<f64> = Canvas::new(70, 40);
+        for (x, y, p) in c.pixels_mut() {
+            *p = Color::new(x as f64 / 69.0, y as f64 / 39.0, if (x + y) % 7 == 0 { 1.5 } else { 0.25 });
+        }
+        for depth in [BitDepth::Eight, BitDepth::Sixteen] {
+            for alpha in [false, true] {
+                let mut png = Vec::new();
+                c.write_png(PngOptions { depth, alpha, compression: Compression::Deflate }, &mut png).unwrap();
+                let chunks = chunks(&png);
+                assert_eq!(chunks.iter().map(|(kind, _)| kind).collect::<Vec<_>>(), [b"IHDR", b"IDAT", b"IEND"]);
+                let raw = zlib_decompress(&chunks[1].1);
+                let channels = if alpha { 4 } else { 3 };
+                let sample_bytes = if depth == BitDepth::Eight { 1 } else { 2 };
+                let stride = 1 + 70 * channels * sample_bytes;
+                assert_eq!(raw.len(), 40 * stride);
+                for (x, y, p) in c.pixels() {
+                    let at = y * stride + 1 + x * channels * sample_bytes;
+                    let sample = |i: usize| raw[at + i * sample_bytes..at + (i + 1) * sample_bytes].iter().fold(0u16, |acc, &b| (acc << 8) | b as u16);
+                    let max = depth.max_value();
+                    assert_eq!([sample(0), sample(1), sample(2)], [p.red(), p.green(), p.blue()].map(|ch| channel_to_level(ch, max)));
+                    if alpha {
+                        assert_eq!(sample(3), max);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn png_rejects_empty_canvases() {
+        for (width, height) in [(0, 0), (3, 0), (0, 2)] {
+            let mut out = Vec::new();
+            let err = Canvas::<f32>::new(width, height).write_png(PngOptions::default(), &mut out).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(out.is_empty());
+        }
+    }
+}
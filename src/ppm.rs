@@ -0,0 +1,305 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::color::{channel_to_level, channel_to_u8, Color};
+use crate::image::BitDepth;
+use crate::scalar::Scalar;
+
+// Netpbm readers may reject longer lines
+const PPM_LINE_WIDTH: usize = 70;
+
+impl<T: Scalar> Canvas<T> {
+    // Plain PPM (P3) with channels clamped and scaled to 0..=255
+    pub fn write_ppm<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "P3")?;
+        writeln!(out, "{} {}", self.width(), self.height())?;
+        writeln!(out, "255")?;
+        for row in self.rows() {
+            let mut line = String::new();
+            for channel in row.iter().flat_map(|c| [c.red(), c.green(), c.blue()]) {
+                let value = channel_to_u8(channel).to_string();
+                if !line.is_empty() && line.len() + 1 + value.len() > PPM_LINE_WIDTH {
+                    writeln!(out, "{}", line)?;
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&value);
+            }
+            if !line.is_empty() {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        out.flush()
+    }
+    pub fn to_ppm(&self) -> String {
+        let mut out = Vec::new();
+        self.write_ppm(&mut out).expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("PPM output is ASCII")
+    }
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_ppm(BufWriter::new(File::create(path)?))
+    }
+    // Raw PPM (P6), 16-bit samples are big-endian
+    pub fn write_binary_ppm<W: Write>(&self, depth: BitDepth, mut out: W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n{}\n", self.width(), self.height(), depth.max_value())?;
+        for (_, _, c) in self.pixels() {
+            for channel in [c.red(), c.green(), c.blue()] {
+                write_sample(&mut out, channel, depth)?;
+            }
+        }
+        out.flush()
+    }
+    // Raw PGM (P5) of each pixel's luminance
+    pub fn write_pgm<W: Write>(&self, depth: BitDepth, mut out: W) -> io::Result<()> {
+        write!(out, "P5\n{} {}\n{}\n", self.width(), self.height(), depth.max_value())?;
+        for (_, _, c) in self.pixels() {
+            write_sample(&mut out, c.luminance(), depth)?;
+        }
+        out.flush()
+    }
+}
+
+fn write_sample<T: Scalar, W: Write>(out: &mut W, channel: T, depth: BitDepth) -> io::Result<()> {
+    let level = channel_to_level(channel, depth.max_value());
+    match depth {
+        BitDepth::Eight => out.write_all(&[level as u8]),
+        BitDepth::Sixteen => out.write_all(&level.to_be_bytes()),
+    }
+}
+
+#[derive(Debug)]
+pub enum PpmError {
+    Io(io::Error),
+    // Anything other than P3 or P6
+    BadMagic(String),
+    // The file ended before the header or all the samples were read
+    Truncated,
+    InvalidNumber(String),
+    InvalidMaxValue(u32),
+    OutOfRange { value: u32, max: u32 },
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PpmError::Io(e) => write!(f, "cannot read PPM: {}", e),
+            PpmError::BadMagic(magic) => write!(f, "unsupported PPM magic number {:?}, expected P3 or P6", magic),
+            PpmError::Truncated => write!(f, "PPM data is truncated"),
+            PpmError::InvalidNumber(token) => write!(f, "{:?} is not a valid PPM number", token),
+            PpmError::InvalidMaxValue(max) => write!(f, "PPM max value {} is not in 1..=65535", max),
+            PpmError::OutOfRange { value, max } => write!(f, "PPM sample {} is above the max value {}", value, max),
+        }
+    }
+}
+
+impl Error for PpmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PpmError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PpmError {
+    fn from(e: io::Error) -> PpmError {
+        PpmError::Io(e)
+    }
+}
+
+// Reads the whitespace separated fields of a Netpbm file, skipping `#` comments
+struct PpmTokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PpmTokens<'a> {
+    fn skip_blanks(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if b == b'#' {
+                while self.pos < self.data.len() && self.data[self.pos] != b'\n' && self.data[self.pos] != b'\r' {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+    fn token(&mut self) -> Result<&'a [u8], PpmError> {
+        self.skip_blanks();
+        let start = self.pos;
+        while self.pos < self.data.len() && !self.data[self.pos].is_ascii_whitespace() && self.data[self.pos] != b'#' {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            Err(PpmError::Truncated)
+        } else {
+            Ok(&self.data[start..self.pos])
+        }
+    }
+    fn number(&mut self) -> Result<u32, PpmError> {
+        let token = self.token()?;
+        let text = String::from_utf8_lossy(token);
+        text.parse().map_err(|_| PpmError::InvalidNumber(text.into_owned()))
+    }
+}
+
+fn sample_to_channel<T: Scalar>(value: u32, max: u32) -> Result<T, PpmError> {
+    if value > max {
+        Err(PpmError::OutOfRange { value, max })
+    } else {
+        Ok(T::from_f64(value as f64 / max as f64))
+    }
+}
+
+impl<T: Scalar> Canvas<T> {
+    // Plain (P3) or raw (P6) PPM, samples are scaled by the max value into 0..1
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas<T>, PpmError> {
+        let mut tokens = PpmTokens { data, pos: 0 };
+        let magic = tokens.token()?;
+        let binary = match magic {
+            b"P3" => false,
+            b"P6" => true,
+            _ => return Err(PpmError::BadMagic(String::from_utf8_lossy(magic).into_owned())),
+        };
+        let width = tokens.number()? as usize;
+        let height = tokens.number()? as usize;
+        let max = tokens.number()?;
+        if max == 0 || max > u16::MAX as u32 {
+            return Err(PpmError::InvalidMaxValue(max));
+        }
+        // The header is not trusted to size allocations, samples are counted as they are read
+        let count = width.checked_mul(height).and_then(|n| n.checked_mul(3)).ok_or(PpmError::Truncated)?;
+        let mut samples = Vec::new();
+        if binary {
+            // A single whitespace byte separates the header from the raster
+            let raster = data.get(tokens.pos + 1..).ok_or(PpmError::Truncated)?;
+            let bytes_per_sample = if max < 256 { 1 } else { 2 };
+            let needed = count * bytes_per_sample;
+            if raster.len() < needed {
+                return Err(PpmError::Truncated);
+            }
+            for sample in raster[..needed].chunks(bytes_per_sample) {
+                let value = sample.iter().fold(0, |acc, &b| (acc << 8) | b as u32);
+                samples.push(sample_to_channel::<T>(value, max)?);
+            }
+        } else {
+            for _ in 0..count {
+                samples.push(sample_to_channel::<T>(tokens.number()?, max)?);
+            }
+        }
+        let mut canvas = Canvas::new(width, height);
+        for ((_, _, pixel), rgb) in canvas.pixels_mut().zip(samples.chunks(3)) {
+            *pixel = Color::new(rgb[0], rgb[1], rgb[2]);
+        }
+        Ok(canvas)
+    }
+    pub fn read_ppm<R: Read>(mut input: R) -> Result<Canvas<T>, PpmError> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+        Canvas::from_ppm(&data)
+    }
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> Result<Canvas<T>, PpmError> {
+        Canvas::read_ppm(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod ppm_tests {
+    use super::PpmError;
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use crate::image::BitDepth;
+
+    #[test]
+    fn ppm_lines_never_exceed_70_characters() {
+        let c: Canvas<f64> = Canvas::filled(10, 2, Color::new(1.0, 0.8, 0.6));
+        let ppm = c.to_ppm();
+        let lines: Vec<&str> = ppm.lines().collect();
+        assert_eq!(lines[3], "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204");
+        assert_eq!(lines[4], "153 255 204 153 255 204 153 255 204 153 255 204 153");
+        assert!(lines.iter().all(|l| l.len() <= 70));
+        assert_eq!(lines.len(), 7);
+        assert!(ppm.ends_with('\n'));
+    }
+
+    #[test]
+    fn ppm_saves_to_a_file() {
+        let mut c: Canvas<f32> = Canvas::new(2, 1);
+        c.write_pixel(1, 0, Color::new(1.5, 0.5, -0.5)).unwrap();
+        let path = std::env::temp_dir().join(format!("ray_tracer_ppm_{}.ppm", std::process::id()));
+        c.save_ppm(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved, "P3\n2 1\n255\n0 0 0 255 128 0\n");
+    }
+
+    #[test]
+    fn ppm_reads_back_what_it_writes() {
+        let mut c: Canvas<f64> = Canvas::new(30, 4);
+        c.write_pixel(29, 3, Color::new(1.0, 0.2, 0.4)).unwrap();
+        c.write_pixel(0, 1, Color::new(0.0, 1.0, 0.0)).unwrap();
+        assert_eq!(Canvas::<f64>::read_ppm(c.to_ppm().as_bytes()).unwrap(), c);
+    }
+
+    #[test]
+    fn ppm_reads_binary_samples() {
+        let mut p6 = b"P6 # raw\n2 1 255\n".to_vec();
+        p6.extend_from_slice(&[255, 0, 51, 0, 0, 255]);
+        let c: Canvas<f64> = Canvas::from_ppm(&p6).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Ok(Color::new(1.0, 0.0, 0.2)));
+        assert_eq!(c.pixel_at(1, 0), Ok(Color::new(0.0, 0.0, 1.0)));
+
+        let mut wide = b"P6\n1 1\n65535\n".to_vec();
+        wide.extend_from_slice(&[0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+        let c: Canvas<f64> = Canvas::from_ppm(&wide).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Ok(Color::new(1.0, 0.0, 32768.0 / 65535.0)));
+    }
+
+    #[test]
+    fn ppm_reports_typed_errors() {
+        let read = |data: &[u8]| Canvas::<f32>::from_ppm(data).unwrap_err();
+        assert!(matches!(read(b"P5\n1 1\n255\n0"), PpmError::BadMagic(m) if m == "P5"));
+        assert!(matches!(read(b"P3\n2 1\n255\n0 0 0 0 0"), PpmError::Truncated));
+        assert!(matches!(read(b"P6\n1 1\n255\n\x00\x00"), PpmError::Truncated));
+        assert!(matches!(read(b"P3\n1 1\n100\n0 101 0"), PpmError::OutOfRange { value: 101, max: 100 }));
+        assert!(matches!(read(b"P3\n1 1\n0\n0 0 0"), PpmError::InvalidMaxValue(0)));
+        assert!(matches!(read(b"P3\n1 x\n255\n0 0 0"), PpmError::InvalidNumber(t) if t == "x"));
+    }
+
+    #[test]
+    fn ppm_binary_output_reads_back_at_both_depths() {
+        let mut c: Canvas<f64> = Canvas::new(3, 2);
+        c.write_pixel(2, 1, Color::new(1.0, 0.2, 0.4)).unwrap();
+        c.write_pixel(0, 1, Color::new(1.5, -1.0, 0.0)).unwrap();
+        let mut clamped = c.clone();
+        clamped.write_pixel(0, 1, Color::new(1.0, 0.0, 0.0)).unwrap();
+        let mut p6 = Vec::new();
+        c.write_binary_ppm(BitDepth::Eight, &mut p6).unwrap();
+        assert_eq!(p6.len(), "P6\n3 2\n255\n".len() + 3 * 2 * 3);
+        assert_eq!(Canvas::<f64>::from_ppm(&p6).unwrap(), clamped);
+        let mut wide = Vec::new();
+        c.write_binary_ppm(BitDepth::Sixteen, &mut wide).unwrap();
+        assert!(wide.starts_with(b"P6\n3 2\n65535\n"));
+        assert_eq!(&wide[wide.len() - 6..], &[0xff, 0xff, 0x33, 0x33, 0x66, 0x66]);
+        assert_eq!(Canvas::<f64>::from_ppm(&wide).unwrap(), clamped);
+    }
+
+    #[test]
+    fn ppm_gray_output_keeps_luminance() {
+        let mut c: Canvas<f64> = Canvas::filled(2, 1, Color::new(0.25, 0.25, 0.25));
+        c.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0)).unwrap();
+        let mut pgm = Vec::new();
+        c.write_pgm(BitDepth::Sixteen, &mut pgm).unwrap();
+        // 0.25 * 65535 and 0.7152 * 65535, rounded
+        assert_eq!(pgm, b"P5\n2 1\n65535\n\x40\x00\xb7\x17");
+    }
+}
@@ -1,53 +1,895 @@
-#[path = "./ray.rs"]
-mod ray;
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::ray::{Point, Vector};
+use crate::Float;
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct Projectile {
-    position: ray::Tuple,
-    velocity: ray::Tuple
+    position: Point,
+    velocity: Vector,
+    // Only forces depend on it, gravity and wind being accelerations
+    mass: Float
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+type Force = dyn Fn(&Point, &Vector) -> Vector;
+
+// Force applied on a projectile at a given position and velocity
+#[derive(Clone)]
+pub struct ForceField(Rc<Force>);
+
+impl ForceField {
+    pub fn new<F: Fn(&Point, &Vector) -> Vector + 'static>(force: F) -> ForceField {
+        ForceField(Rc::new(force))
+    }
+    pub fn force(&self, position: &Point, velocity: &Vector) -> Vector {
+        (self.0)(position, velocity)
+    }
+}
+
+impl fmt::Debug for ForceField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ForceField")
+    }
+}
+
+// Closures cannot be compared, two fields are the same when they share their closure
+impl PartialEq for ForceField {
+    fn eq(&self, other: &ForceField) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Environment {
-    gravity: ray::Tuple,
-    wind: ray::Tuple
+    gravity: Vector,
+    // The constant push of the book, an acceleration like gravity
+    wind: Vector,
+    // Velocity of the air the projectile moves through, only drag depends on it
+    air_velocity: Vector,
+    // Drag force is -(linear + quadratic * |v|) * v with v the velocity relative to the air
+    linear_drag: Float,
+    quadratic_drag: Float,
+    force_fields: Vec<ForceField>
+}
+
+impl Projectile {
+    pub fn new(position: Point, velocity: Vector) -> Projectile {
+        Projectile { position, velocity, mass: 1.0 }
+    }
+    pub fn with_position(self, position: Point) -> Projectile {
+        Projectile { position, ..self }
+    }
+    pub fn with_velocity(self, velocity: Vector) -> Projectile {
+        Projectile { velocity, ..self }
+    }
+    // Forces are divided by the mass, which must be a positive number
+    pub fn with_mass(self, mass: Float) -> Projectile {
+        assert!(mass.is_finite() && mass > 0.0, "mass {} must be a positive finite number", mass);
+        Projectile { mass, ..self }
+    }
+    pub fn position(&self) -> &Point {
+        &self.position
+    }
+    pub fn velocity(&self) -> &Vector {
+        &self.velocity
+    }
+    pub fn mass(&self) -> Float {
+        self.mass
+    }
+}
+
+impl Default for Projectile {
+    // At rest on the origin
+    fn default() -> Projectile {
+        Projectile::new(Point::origin(), Vector::zero())
+    }
+}
+
+impl Environment {
+    // Without drag nor other forces
+    pub fn new(gravity: Vector, wind: Vector) -> Environment {
+        Environment { gravity, wind, air_velocity: Vector::zero(), linear_drag: 0.0, quadratic_drag: 0.0, force_fields: Vec::new() }
+    }
+    pub fn with_gravity(self, gravity: Vector) -> Environment {
+        Environment { gravity, ..self }
+    }
+    pub fn with_wind(self, wind: Vector) -> Environment {
+        Environment { wind, ..self }
+    }
+    pub fn with_air_velocity(self, air_velocity: Vector) -> Environment {
+        Environment { air_velocity, ..self }
+    }
+    pub fn with_drag(self, linear_drag: Float, quadratic_drag: Float) -> Environment {
+        Environment { linear_drag, quadratic_drag, ..self }
+    }
+    pub fn with_force_field(mut self, field: ForceField) -> Environment {
+        self.force_fields.push(field);
+        self
+    }
+    pub fn gravity(&self) -> &Vector {
+        &self.gravity
+    }
+    pub fn wind(&self) -> &Vector {
+        &self.wind
+    }
+    pub fn air_velocity(&self) -> &Vector {
+        &self.air_velocity
+    }
+    pub fn linear_drag(&self) -> Float {
+        self.linear_drag
+    }
+    pub fn quadratic_drag(&self) -> Float {
+        self.quadratic_drag
+    }
+    pub fn force_fields(&self) -> &[ForceField] {
+        &self.force_fields
+    }
+    // Sum of the drag and of every force field
+    pub fn force(&self, position: &Point, velocity: &Vector) -> Vector {
+        let relative = *velocity - self.air_velocity;
+        let drag = relative * -(self.linear_drag + self.quadratic_drag * relative.magnitude());
+        self.force_fields.iter().fold(drag, |total, field| total + field.force(position, velocity))
+    }
+    pub fn acceleration(&self, mass: Float, position: &Point, velocity: &Vector) -> Vector {
+        self.gravity + self.wind + self.force(position, velocity) / mass
+    }
+}
+
+impl Default for Environment {
+    // The world `launch` has always used
+    fn default() -> Environment {
+        Environment::new(Vector::new(0.0, -0.1, 0.0), Vector::new(-0.001, 0.0, 0.0))
+    }
+}
+
+// Scheme advancing a projectile by one time step under an acceleration that may depend on its state
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Integrator {
+    // Position from the old velocity then velocity, first order, what the book does
+    #[default]
+    ExplicitEuler,
+    // Velocity first then position from the new velocity, first order but stable for orbits and springs
+    SemiImplicitEuler,
+    // Second order, exact under a constant acceleration
+    VelocityVerlet,
+    // Classic fourth order Runge-Kutta
+    Rk4,
+}
+
+impl Integrator {
+    pub fn step<F: Fn(&Point, &Vector) -> Vector>(&self, proj: &Projectile, dt: Float, acceleration: F) -> Projectile {
+        let (x, v) = (proj.position, proj.velocity);
+        let moved = |position, velocity| Projectile { position, velocity, ..proj.clone() };
+        match self {
+            Integrator::ExplicitEuler => moved(x + v * dt, v + acceleration(&x, &v) * dt),
+            Integrator::SemiImplicitEuler => {
+                let velocity = v + acceleration(&x, &v) * dt;
+                moved(x + velocity * dt, velocity)
+            }
+            Integrator::VelocityVerlet => {
+                let a = acceleration(&x, &v);
+                let position = x + v * dt + a * (0.5 * dt * dt);
+                // The new acceleration may depend on the velocity, estimated with an Euler step
+                let next = acceleration(&position, &(v + a * dt));
+                moved(position, v + (a + next) * (0.5 * dt))
+            }
+            Integrator::Rk4 => {
+                let half = 0.5 * dt;
+                let (k1x, k1v) = (v, acceleration(&x, &v));
+                let (k2x, k2v) = (v + k1v * half, acceleration(&(x + k1x * half), &(v + k1v * half)));
+                let (k3x, k3v) = (v + k2v * half, acceleration(&(x + k2x * half), &(v + k2v * half)));
+                let (k4x, k4v) = (v + k3v * dt, acceleration(&(x + k3x * dt), &(v + k3v * dt)));
+                moved(x + (k1x + k2x * 2.0 + k3x * 2.0 + k4x) * (dt / 6.0), v + (k1v + k2v * 2.0 + k3v * 2.0 + k4v) * (dt / 6.0))
+            }
+        }
+    }
+}
+
+// What happens when the projectile reaches the floor at y = 0
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Ground {
+    // Share of the vertical speed kept when bouncing, 0 stops the projectile where it lands
+    pub restitution: Float,
+    // Coulomb coefficient, each bounce takes friction times the vertical impulse from the horizontal speed,
+    // and sliding along the floor friction times the pull into it
+    pub friction: Float,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StopConditions {
+    pub max_ticks: Option<usize>,
+    pub max_time: Option<Float>,
+    // Below this speed a bounce does not leave the ground, and a projectile that cannot gain it within a tick is at rest,
+    // the floor cancelling gravity and friction holding it there
+    pub resting_speed: Float,
+}
+
+impl Default for StopConditions {
+    // The tick limit keeps a projectile that never falls from running forever
+    fn default() -> StopConditions {
+        StopConditions { max_ticks: Some(100_000), max_time: None, resting_speed: 0.001 }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Termination {
+    // Reached the floor without bouncing back, nor friction to stop it sliding
+    Landed,
+    // Almost still, with no acceleration in the air or held by friction on the floor
+    Resting,
+    MaxTicks,
+    MaxTime,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LaunchOptions {
+    // By default the initial velocity is normalized, only its direction counts
+    pub keep_velocity_magnitude: bool,
+    pub integrator: Integrator,
+    // Duration of a tick, gravity and wind being accelerations per squared tick
+    pub dt: Float,
+    pub ground: Ground,
+    pub stop: StopConditions,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> LaunchOptions {
+        LaunchOptions { keep_velocity_magnitude: false, integrator: Integrator::ExplicitEuler, dt: 1.0, ground: Ground::default(), stop: StopConditions::default() }
+    }
+}
+
+impl LaunchOptions {
+    pub fn keep_velocity_magnitude(self) -> LaunchOptions {
+        LaunchOptions { keep_velocity_magnitude: true, ..self }
+    }
+    pub fn with_integrator(self, integrator: Integrator) -> LaunchOptions {
+        LaunchOptions { integrator, ..self }
+    }
+    pub fn with_dt(self, dt: Float) -> LaunchOptions {
+        LaunchOptions { dt, ..self }
+    }
+    pub fn with_ground(self, restitution: Float, friction: Float) -> LaunchOptions {
+        LaunchOptions { ground: Ground { restitution, friction }, ..self }
+    }
+    pub fn with_stop(self, stop: StopConditions) -> LaunchOptions {
+        LaunchOptions { stop, ..self }
+    }
+}
+
+fn ticks(env: &Environment, proj: &Projectile, options: &LaunchOptions, dt: Float) -> Projectile {
+    options.integrator.step(proj, dt, |position, velocity| env.acceleration(proj.mass, position, velocity))
+}
+
+// Where the projectile is after `t` under gravity and wind alone, what the integrators approximate
+pub fn parabola_at(env: &Environment, initial: &Projectile, t: Float) -> Projectile {
+    let a = env.gravity + env.wind;
+    initial.clone().with_position(initial.position + initial.velocity * t + a * (0.5 * t * t)).with_velocity(initial.velocity + a * t)
+}
+
+// Samples of a launch with the time each was taken at and why the simulation stopped
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trajectory {
+    pub samples: Vec<Projectile>,
+    pub times: Vec<Float>,
+    pub termination: Termination,
+}
+
+impl Trajectory {
+    // (tick, time, sample), ticks counting from 1 as the initial state is not a sample
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Float, &Projectile)> {
+        self.samples.iter().zip(self.times.iter()).enumerate().map(|(i, (p, t))| (i + 1, *t, p))
+    }
+}
+
+// Takes `by` from the speed, stopping rather than going backwards
+fn slow_down(velocity: Vector, by: Float) -> Vector {
+    let speed = velocity.magnitude();
+    if by >= speed { Vector::zero() } else { velocity * (1.0 - by / speed) }
+}
+
+// Velocity leaving the floor: vertical speed reversed and scaled by the restitution, horizontal speed slowed by friction
+fn bounce(velocity: &Vector, ground: &Ground) -> Vector {
+    let normal_speed = velocity.y().abs();
+    let tangent = slow_down(Vector::new(velocity.x(), 0.0, velocity.z()), ground.friction * (1.0 + ground.restitution) * normal_speed);
+    tangent + Vector::new(0.0, ground.restitution * normal_speed, 0.0)
+}
+
+// How a projectile hitting the floor carries on
+enum Contact {
+    Bounce(Vector),
+    // Too slow to leave the floor, friction will stop it
+    Slide(Vector),
+    // Too slow to leave the floor and nothing to stop it sliding
+    Land,
+}
+
+fn touch_down(impact: &Vector, ground: &Ground, resting_speed: Float) -> Contact {
+    let rebound = bounce(impact, ground);
+    // A rebound of 0 never leaves the floor, even with no resting speed
+    if rebound.y() > 0.0 && rebound.y() >= resting_speed {
+        Contact::Bounce(rebound)
+    } else if ground.friction > 0.0 {
+        Contact::Slide(Vector::new(rebound.x(), 0.0, rebound.z()))
+    } else {
+        Contact::Land
+    }
+}
+
+// Pull into the floor, cancelled by the floor, and what is left along it
+fn floor_forces(env: &Environment, proj: &Projectile) -> (Float, Vector) {
+    let a = env.acceleration(proj.mass, &proj.position, &proj.velocity);
+    (-a.y(), Vector::new(a.x(), 0.0, a.z()))
+}
+
+// A step along the floor, friction taking friction times the pull into the floor from the speed
+fn slide(env: &Environment, proj: &Projectile, options: &LaunchOptions, dt: Float) -> Projectile {
+    let (pull, _) = floor_forces(env, proj);
+    let next = options.integrator.step(proj, dt, |position, velocity| {
+        let a = env.acceleration(proj.mass, position, velocity);
+        Vector::new(a.x(), 0.0, a.z())
+    });
+    let velocity = slow_down(Vector::new(next.velocity.x(), 0.0, next.velocity.z()), options.ground.friction * pull.max(0.0) * dt);
+    let position = Point::new(next.position.x(), 0.0, next.position.z());
+    next.with_position(position).with_velocity(velocity)
+}
+
+// Samples after each tick until a stop condition, bounces happening within a tick; only a landing is sampled at its contact time
+pub fn launch_with(env: &Environment, proj: &Projectile, options: &LaunchOptions) -> Trajectory {
+    let stop = &options.stop;
+    // Time would not move forward, or never reach the limit
+    assert!(options.dt.is_finite() && options.dt > 0.0, "time step {} must be a positive finite number", options.dt);
+    assert!(stop.max_time.is_none_or(|max| max.is_finite()), "time limit {:?} must be a finite number", stop.max_time);
+    let mut moving = proj.clone();
+    if !options.keep_velocity_magnitude {
+        moving.velocity = moving.velocity.normalize();
+    }
+    let mut trajectory = Trajectory { samples: Vec::new(), times: Vec::new(), termination: Termination::MaxTicks };
+    let mut sliding = false;
+    // Starting on or under the floor puts the projectile on it, where it lands unless it is thrown upwards, bounces or slides
+    if moving.position.y() <= 0.0 {
+        moving.position = Point::new(moving.position.x(), 0.0, moving.position.z());
+        if moving.velocity.y() <= 0.0 {
+            match touch_down(&moving.velocity, &options.ground, stop.resting_speed) {
+                Contact::Bounce(rebound) => moving.velocity = rebound,
+                Contact::Slide(velocity) => {
+                    moving.velocity = velocity;
+                    sliding = true;
+                }
+                Contact::Land => {
+                    trajectory.termination = Termination::Landed;
+                    return trajectory;
+                }
+            }
+        }
+    }
+    loop {
+        let resting = if sliding {
+            // Static friction holds it when the pull along the floor is within friction times the pull into it
+            let (pull, along) = floor_forces(env, &moving);
+            moving.velocity.magnitude() < stop.resting_speed && along.magnitude() <= options.ground.friction * pull
+        } else {
+            let acceleration = env.acceleration(moving.mass, &moving.position, &moving.velocity);
+            moving.velocity.magnitude() < stop.resting_speed && acceleration.magnitude() * options.dt < stop.resting_speed
+        };
+        if resting {
+            trajectory.termination = Termination::Resting;
+            break;
+        }
+        if stop.max_ticks.is_some_and(|max| trajectory.samples.len() >= max) {
+            trajectory.termination = Termination::MaxTicks;
+            break;
+        }
+        // Times come from the tick count rather than a running sum, which would drift
+        let tick = trajectory.samples.len();
+        let (start, end) = (tick as Float * options.dt, (tick + 1) as Float * options.dt);
+        // A limit a tiny share of a step away counts as reached, rather than adding a tick of almost no time
+        let close_to = |max: Float, time: Float| max - time <= options.dt * 0.0001;
+        if stop.max_time.is_some_and(|max| close_to(max, start)) {
+            trajectory.termination = Termination::MaxTime;
+            break;
+        }
+        // The last tick is shortened or stretched to end right on the time limit
+        let end = match stop.max_time {
+            Some(max) if close_to(max, end) => max,
+            _ => end,
+        };
+        // What is left of the tick, a bounce splitting it in two
+        let mut remaining = end - start;
+        while remaining > 0.0 {
+            if sliding {
+                if floor_forces(env, &moving).0 < 0.0 {
+                    // Pulled up, it flies off for the rest of the tick
+                    sliding = false;
+                    continue;
+                }
+                moving = slide(env, &moving, options, remaining);
+                remaining = 0.0;
+                continue;
+            }
+            let next = ticks(env, &moving, options, remaining);
+            let (height, next_height) = (moving.position.y(), next.position.y());
+            if height >= 0.0 && next_height < 0.0 {
+                // Share of the step spent above the floor, the motion being taken as straight within a step
+                let s = height / (height - next_height);
+                let contact = Point::new(moving.position.x() + (next.position.x() - moving.position.x()) * s, 0.0, moving.position.z() + (next.position.z() - moving.position.z()) * s);
+                let mut impact = moving.velocity + (next.velocity - moving.velocity) * s;
+                if s == 0.0 && moving.velocity.y() > 0.0 {
+                    // Thrown back into the floor within the step, the rebound is too weak to leave it
+                    impact = Vector::new(impact.x(), 0.0, impact.z());
+                }
+                remaining -= remaining * s;
+                moving = next.with_position(contact).with_velocity(impact);
+                match touch_down(&impact, &options.ground, stop.resting_speed) {
+                    Contact::Bounce(rebound) => moving.velocity = rebound,
+                    Contact::Slide(velocity) => {
+                        moving.velocity = velocity;
+                        sliding = true;
+                    }
+                    Contact::Land => {
+                        trajectory.samples.push(moving);
+                        trajectory.times.push(end - remaining);
+                        trajectory.termination = Termination::Landed;
+                        return trajectory;
+                    }
+                }
+            } else {
+                remaining = 0.0;
+                moving = next;
+            }
+        }
+        trajectory.times.push(end);
+        trajectory.samples.push(moving.clone());
+    }
+    trajectory
+}
+
+// The book's launch: normalized velocity, default world, until the projectile lands
+pub fn launch(pos_x: Float, pos_y: Float, pos_z: Float, v_x: Float, v_y: Float, v_z: Float) -> Vec<Projectile> {
+    let initial = Projectile::new(Point::new(pos_x, pos_y, pos_z), Vector::new(v_x, v_y, v_z));
+    launch_with(&Environment::default(), &initial, &LaunchOptions::default()).samples
 }
 
-fn ticks(env: &Environment, proj: &Projectile) -> Projectile {
-    let position = proj.position.add(&proj.velocity);
-    let velocity = proj.velocity.add(&env.gravity).add(&env.wind);
-    Projectile {
-        position: position,
-        velocity: velocity
+// JSON has no NaN nor infinity
+fn json_number(value: Float) -> String {
+    if value.is_finite() { value.to_string() } else { "null".to_string() }
+}
+
+impl Trajectory {
+    // One line per sample after a header, written as the samples are read
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "tick,time,x,y,z,vx,vy,vz,speed")?;
+        for (tick, time, p) in self.iter() {
+            let (x, v) = (p.position, p.velocity);
+            writeln!(out, "{},{},{},{},{},{},{},{},{}", tick, time, x.x(), x.y(), x.z(), v.x(), v.y(), v.z(), v.magnitude())?;
+        }
+        out.flush()
+    }
+    // An array with one object per line, non-finite values being written as null
+    pub fn write_json<W: Write>(&self, mut out: W) -> io::Result<()> {
+        write!(out, "[")?;
+        for (tick, time, p) in self.iter() {
+            let (x, v) = (p.position, p.velocity);
+            let triple = |a: Float, b: Float, c: Float| format!("[{}, {}, {}]", json_number(a), json_number(b), json_number(c));
+            write!(
+                out,
+                "{}\n  {{\"tick\": {}, \"time\": {}, \"position\": {}, \"velocity\": {}, \"speed\": {}}}",
+                if tick == 1 { "" } else { "," },
+                tick,
+                json_number(time),
+                triple(x.x(), x.y(), x.z()),
+                triple(v.x(), v.y(), v.z()),
+                json_number(v.magnitude())
+            )?;
+        }
+        writeln!(out, "{}]", if self.samples.is_empty() { "" } else { "\n" })?;
+        out.flush()
+    }
+}
+
+// How `plot_trajectory` lays out the x/y plane of a trajectory on a canvas
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PlotOptions {
+    pub width: usize,
+    pub height: usize,
+    // Blank pixels kept around the plotted area
+    pub margin: usize,
+    // Each sample is drawn as a square of 2 * radius + 1 pixels
+    pub dot_radius: usize,
+    // Lines through the origin, which is then always in view
+    pub axes: bool,
+    // Fills everything below y = 0
+    pub ground: bool,
+    pub background: Color,
+    pub trajectory_color: Color,
+    pub axes_color: Color,
+    pub ground_color: Color,
+}
+
+impl Default for PlotOptions {
+    // Size of the cannon ball figure of the book
+    fn default() -> PlotOptions {
+        PlotOptions {
+            width: 900,
+            height: 550,
+            margin: 10,
+            dot_radius: 1,
+            axes: true,
+            ground: true,
+            background: Color::black(),
+            trajectory_color: Color::new(1.0, 0.2, 0.2),
+            axes_color: Color::new(0.5, 0.5, 0.5),
+            ground_color: Color::new(0.3, 0.2, 0.1),
+        }
     }
 }
 
-pub fn launch(pos_x: f32, pos_y:f32, pos_z: f32, v_x: f32, v_y: f32, v_z: f32) -> Vec<Projectile> {
-    let initial = Projectile {
-        position: ray::Tuple::point3(pos_x, pos_y, pos_z),
-        velocity: ray::Tuple::vector3(v_x, v_y, v_z).normalize()
+// Plots the x/y position of each sample with y pointing up, one scale on both axes so the curve keeps its shape
+pub fn plot_trajectory(pos_by_ticks: &[Projectile], options: &PlotOptions) -> Canvas {
+    let mut canvas = Canvas::filled(options.width, options.height, options.background);
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = if options.axes || options.ground { (0.0, 0.0, 0.0, 0.0) } else { (Float::INFINITY, Float::NEG_INFINITY, Float::INFINITY, Float::NEG_INFINITY) };
+    for p in pos_by_ticks {
+        min_x = p.position.x().min(min_x);
+        max_x = p.position.x().max(max_x);
+        min_y = p.position.y().min(min_y);
+        max_y = p.position.y().max(max_y);
+    }
+    if min_x > max_x {
+        return canvas;
+    }
+    let plot_width = options.width.saturating_sub(2 * options.margin + 1) as Float;
+    let plot_height = options.height.saturating_sub(2 * options.margin + 1) as Float;
+    let scale = match (max_x - min_x, max_y - min_y) {
+        (dx, dy) if dx > 0.0 && dy > 0.0 => (plot_width / dx).min(plot_height / dy),
+        (dx, _) if dx > 0.0 => plot_width / dx,
+        (_, dy) if dy > 0.0 => plot_height / dy,
+        _ => 1.0,
+    };
+    // Pixel column and row of a world position, the canvas row 0 being at the top
+    let to_canvas = |x: Float, y: Float| -> (isize, isize) {
+        let column = options.margin as Float + (x - min_x) * scale;
+        let row = options.height.saturating_sub(options.margin + 1) as Float - (y - min_y) * scale;
+        (column.round() as isize, row.round() as isize)
     };
-    let env = Environment {
-        gravity: ray::Tuple::vector3(0.0, -0.1, 0.0),
-        wind: ray::Tuple::vector3(-0.001, 0.0, 0.0)
+    let mut paint = |column: isize, row: isize, color: Color| {
+        if column >= 0 && row >= 0 {
+            // Anything beyond the canvas is clipped
+            let _ = canvas.write_pixel(column as usize, row as usize, color);
+        }
     };
 
-    let mut moving = initial.clone();
-    let mut vec = Vec::new();
-    while moving.position.y() > 0.0 {
-        moving = ticks(&env, &moving);
-        vec.push(moving.clone());
+    let (origin_column, origin_row) = to_canvas(0.0, 0.0);
+    if options.ground {
+        for row in origin_row.max(0)..options.height as isize {
+            for column in 0..options.width as isize {
+                paint(column, row, options.ground_color);
+            }
+        }
+    }
+    if options.axes {
+        for column in 0..options.width as isize {
+            paint(column, origin_row, options.axes_color);
+        }
+        for row in 0..options.height as isize {
+            paint(origin_column, row, options.axes_color);
+        }
+    }
+    let radius = options.dot_radius as isize;
+    for p in pos_by_ticks {
+        let (column, row) = to_canvas(p.position.x(), p.position.y());
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                paint(column + dx, row + dy, options.trajectory_color);
+            }
+        }
     }
-    vec
+    canvas
 }
 
-pub fn trace_trajectory(pos_by_ticks: &Vec<Projectile>) {
-    let full_trace = pos_by_ticks.iter().fold(String::new(), |acc, w| {
-        if acc.is_empty() {
-            format!("{:?}", w)
-        } else {
-            format!("{}\n{:?}", acc, w)
+#[cfg(test)]
+mod projectile_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn projectile_launch_ends_on_floor() {
+        let trajectory = launch(0.0, 1.0, 0.0, 1.0, 1.0, 0.0);
+        let last = trajectory.last().unwrap();
+        assert!(last.position().y() <= 0.0);
+        assert!(last.velocity().y() < 0.0);
+    }
+
+    #[test]
+    fn projectile_plot_flips_y_and_fits_the_canvas() {
+        let samples = [(0.0, 0.0), (2.0, 1.0), (4.0, 0.0)]
+            .iter()
+            .map(|&(x, y)| Projectile::new(Point::new(x, y, 0.0), Vector::zero()))
+            .collect::<Vec<_>>();
+        let options = PlotOptions { width: 11, height: 8, margin: 1, dot_radius: 0, ..PlotOptions::default() };
+        let canvas = plot_trajectory(&samples, &options);
+        // 8 pixels for 4 units across, 5 pixels for 1 unit up: x sets the scale, 2 pixels per unit
+        assert_eq!(canvas.pixel_at(1, 6), Ok(options.trajectory_color));
+        assert_eq!(canvas.pixel_at(5, 4), Ok(options.trajectory_color));
+        assert_eq!(canvas.pixel_at(9, 6), Ok(options.trajectory_color));
+        assert_eq!(canvas.pixel_at(1, 3), Ok(options.axes_color));
+        assert_eq!(canvas.pixel_at(5, 6), Ok(options.axes_color));
+        assert_eq!(canvas.pixel_at(5, 7), Ok(options.ground_color));
+        assert_eq!(canvas.pixel_at(5, 0), Ok(options.background));
+
+        let bare = plot_trajectory(&samples, &PlotOptions { axes: false, ground: false, ..options });
+        assert_eq!(bare.pixels().filter(|(_, _, c)| **c != options.background).count(), 3);
+    }
+
+    #[test]
+    fn projectile_plot_keeps_the_whole_launch_in_view() {
+        let trajectory = launch(0.0, 1.0, 0.0, 1.0, 1.0, 0.0);
+        let canvas = plot_trajectory(&trajectory, &PlotOptions::default());
+        let lit = canvas.pixels().filter(|(_, _, c)| **c == PlotOptions::default().trajectory_color).count();
+        assert!(lit >= trajectory.len());
+        let empty = plot_trajectory(&[], &PlotOptions { axes: false, ground: false, ..PlotOptions::default() });
+        assert!(empty.pixels().all(|(_, _, c)| *c == Color::black()));
+    }
+
+    #[test]
+    fn projectile_plot_survives_degenerate_sizes() {
+        let trajectory = launch(0.0, 1.0, 0.0, 1.0, 1.0, 0.0);
+        for (width, height, margin) in [(0, 0, 0), (5, 0, 0), (0, 5, 0), (4, 3, 10), (1, 1, 0)] {
+            let canvas = plot_trajectory(&trajectory, &PlotOptions { width, height, margin, ..PlotOptions::default() });
+            assert_eq!((canvas.width(), canvas.height()), (width, height));
         }
-    });
-    println!("{}", full_trace);
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn projectile_launch_with_keeps_velocity_magnitude_on_request() {
+        let initial = Projectile::default().with_position(Point::new(0.0, 1.0, 0.0)).with_velocity(Vector::new(5.0, 3.0, 0.0));
+        let env = Environment::default();
+        assert_eq!(launch_with(&env, &initial, &LaunchOptions::default()).samples, launch(0.0, 1.0, 0.0, 5.0, 3.0, 0.0));
+
+        let fast = launch_with(&env, &initial, &LaunchOptions::default().keep_velocity_magnitude()).samples;
+        assert_eq!(fast[0].position(), &Point::new(5.0, 4.0, 0.0));
+        assert_eq!(fast[0].velocity(), &Vector::new(4.999, 2.9, 0.0));
+        assert!(fast.len() > launch(0.0, 1.0, 0.0, 5.0, 3.0, 0.0).len());
+    }
+
+    #[test]
+    fn projectile_environment_is_configurable() {
+        let initial = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let env = Environment::default().with_gravity(Vector::new(0.0, -0.5, 0.0)).with_wind(Vector::zero());
+        let trajectory = launch_with(&env, &initial, &LaunchOptions::default());
+        assert_eq!(trajectory.iter().map(|(_, t, p)| (t, p.position().y())).collect::<Vec<_>>(), vec![(1.0, 1.0), (2.0, 0.5), (2.5, 0.0)]);
+        // Contact half way through the third tick
+        assert_eq!(trajectory.samples[2].position().x(), 2.5);
+        assert_eq!(trajectory.termination, Termination::Landed);
+    }
+
+    #[test]
+    fn projectile_integrators_against_the_parabola() {
+        let env = Environment::default().with_wind(Vector::new(-0.01, 0.0, 0.0));
+        let initial = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 1.8, 0.0));
+        let duration = 20.0;
+        let exact = parabola_at(&env, &initial, duration);
+        let error = |integrator: Integrator, dt: Float| {
+            let mut moving = initial.clone();
+            for _ in 0..(duration / dt).round() as usize {
+                moving = integrator.step(&moving, dt, |_, _| *env.gravity() + *env.wind());
+            }
+            (moving.position - exact.position).magnitude()
+        };
+        for integrator in [Integrator::ExplicitEuler, Integrator::SemiImplicitEuler, Integrator::VelocityVerlet, Integrator::Rk4] {
+            let (coarse, fine) = (error(integrator, 1.0), error(integrator, 0.5));
+            match integrator {
+                // First order, halving the step halves the error
+                Integrator::ExplicitEuler | Integrator::SemiImplicitEuler => assert!((coarse / fine - 2.0).abs() < 0.01, "{:?}: {} / {}", integrator, coarse, fine),
+                _ => assert!(coarse < 0.001 && fine < 0.001, "{:?}: {} / {}", integrator, coarse, fine),
+            }
+        }
+    }
+
+    #[test]
+    fn projectile_launch_with_a_smaller_step_follows_the_parabola() {
+        let env = Environment::default();
+        let initial = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 1.0, 0.0));
+        let options = LaunchOptions::default().keep_velocity_magnitude().with_integrator(Integrator::Rk4).with_dt(0.25);
+        let trajectory = launch_with(&env, &initial, &options);
+        let (landing, flight) = trajectory.samples.split_last().unwrap();
+        for (tick, sample) in flight.iter().enumerate() {
+            assert_approx_eq!(*sample.position(), *parabola_at(&env, &initial, (tick + 1) as Float * 0.25).position(), epsilon = 0.0001);
+        }
+        assert_eq!(landing.position().y(), 0.0);
+    }
+
+    #[test]
+    fn projectile_drag_reaches_terminal_velocity() {
+        let falling = |env: &Environment| {
+            let options = LaunchOptions::default().keep_velocity_magnitude().with_integrator(Integrator::Rk4).with_dt(0.1);
+            (0..500).fold(Projectile::default().with_mass(2.0), |p, _| ticks(env, &p, &options, options.dt))
+        };
+        let still_air = Environment::new(Vector::new(0.0, -1.0, 0.0), Vector::zero());
+        // m * g / k and sqrt(m * g / c)
+        assert_approx_eq!(*falling(&still_air.clone().with_drag(0.5, 0.0)).velocity(), Vector::new(0.0, -4.0, 0.0), epsilon = 0.001);
+        assert_approx_eq!(*falling(&still_air.with_drag(0.0, 0.5)).velocity(), Vector::new(0.0, -2.0, 0.0), epsilon = 0.001);
+    }
+
+    #[test]
+    fn projectile_drag_is_relative_to_the_air() {
+        let env = Environment::new(Vector::zero(), Vector::zero()).with_air_velocity(Vector::new(2.0, 0.0, 0.0)).with_drag(0.5, 0.25);
+        assert_eq!(env.force(&Point::origin(), &Vector::new(2.0, 0.0, 0.0)), Vector::zero());
+        assert_eq!(env.force(&Point::origin(), &Vector::new(0.0, 0.0, 0.0)), Vector::new(2.0, 0.0, 0.0));
+        assert_eq!(env.acceleration(2.0, &Point::origin(), &Vector::new(0.0, 0.0, 0.0)), Vector::new(1.0, 0.0, 0.0));
+
+        // Carried by the air, a projectile at rest ends up moving with it
+        let options = LaunchOptions::default().keep_velocity_magnitude().with_integrator(Integrator::Rk4).with_dt(0.1);
+        let carried = (0..300).fold(Projectile::default(), |p, _| ticks(&env, &p, &options, options.dt));
+        assert_approx_eq!(*carried.velocity(), *env.air_velocity(), epsilon = 0.001);
+    }
+
+    #[test]
+    #[should_panic(expected = "mass 0 must be a positive finite number")]
+    fn projectile_mass_must_be_positive() {
+        Projectile::default().with_mass(0.0);
+    }
+
+    #[test]
+    fn projectile_mass_rejects_negative_and_non_finite_values() {
+        for mass in [-1.0, Float::NAN, Float::INFINITY] {
+            assert!(std::panic::catch_unwind(|| Projectile::default().with_mass(mass)).is_err(), "{}", mass);
+        }
+    }
+
+    #[test]
+    fn projectile_mass_only_scales_forces() {
+        let heavy = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 1.0, 0.0)).with_mass(10.0);
+        let options = LaunchOptions::default();
+        let positions = |trajectory: Vec<Projectile>| trajectory.iter().map(|p| *p.position()).collect::<Vec<_>>();
+        assert_eq!(positions(launch_with(&Environment::default(), &heavy, &options).samples), positions(launch(0.0, 1.0, 0.0, 1.0, 1.0, 0.0)));
+
+        // An upward force of 3 holds a mass of 3 against a gravity of 1
+        let lift = ForceField::new(|_, _| Vector::new(0.0, 3.0, 0.0));
+        let env = Environment::new(Vector::new(0.0, -1.0, 0.0), Vector::zero()).with_force_field(lift.clone());
+        assert_eq!(env.force_fields(), &[lift]);
+        let hovering = (0..10).fold(Projectile::default().with_mass(3.0).with_velocity(Vector::new(1.0, 0.0, 0.0)), |p, _| ticks(&env, &p, &options, options.dt));
+        assert_eq!(hovering.position(), &Point::new(10.0, 0.0, 0.0));
+        assert_eq!(hovering.mass(), 3.0);
+    }
+
+    #[test]
+    fn projectile_bounces_until_it_stays_on_the_floor() {
+        let env = Environment::new(Vector::new(0.0, -1.0, 0.0), Vector::zero());
+        let dropped = Projectile::new(Point::new(0.0, 2.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let options = LaunchOptions::default().keep_velocity_magnitude().with_integrator(Integrator::VelocityVerlet).with_dt(0.01).with_ground(0.5, 0.0);
+        let trajectory = launch_with(&env, &dropped, &options);
+        // Each rebound keeps half the vertical speed, so a quarter of the height
+        let apexes: Vec<Float> = trajectory.samples.windows(3).filter(|w| w[1].position().y() >= w[0].position().y() && w[1].position().y() > w[2].position().y()).map(|w| w[1].position().y()).collect();
+        assert!(apexes.len() > 3);
+        assert_approx_eq!(apexes[0], 0.5, epsilon = 0.01);
+        assert_approx_eq!(apexes[1], 0.125, epsilon = 0.01);
+        assert_eq!(trajectory.termination, Termination::Landed);
+        assert!(trajectory.samples.iter().all(|p| p.position().y() >= 0.0));
+        // Bounces happen within ticks, the samples keep to the time step
+        let (_, ticking) = trajectory.times.split_last().unwrap();
+        assert!(ticking.iter().enumerate().all(|(i, t)| (t - (i + 1) as Float * 0.01).abs() < 0.001));
+
+        let rough = launch_with(&env, &dropped, &options.with_ground(0.5, 0.25));
+        // Friction takes 0.25 * 1.5 * 2 from the horizontal speed of 1 on the first bounce
+        let first = rough.samples.iter().find(|p| p.velocity().y() > 0.0).unwrap();
+        assert_approx_eq!(first.velocity().x(), 0.25, epsilon = 0.02);
+        // and the second bounce, taking 0.375, stops it
+        assert_eq!(rough.samples.last().unwrap().velocity().x(), 0.0);
+        assert_eq!(rough.termination, Termination::Resting);
+    }
+
+    #[test]
+    fn projectile_friction_brings_it_to_rest_on_the_floor() {
+        let env = Environment::new(Vector::new(0.0, -1.0, 0.0), Vector::zero());
+        let options = LaunchOptions::default().keep_velocity_magnitude().with_dt(0.01).with_ground(0.0, 0.5);
+        let rolling = launch_with(&env, &Projectile::new(Point::new(0.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0)), &options);
+        assert_eq!(rolling.termination, Termination::Resting);
+        // Friction takes 0.5 * g from the speed of 2 each unit of time, it stops after 4 units
+        assert_approx_eq!(rolling.samples.last().unwrap().position().x(), 4.0, epsilon = 0.05);
+        assert_approx_eq!(*rolling.times.last().unwrap(), 4.0, epsilon = 0.05);
+        assert!(rolling.samples.iter().all(|p| p.position().y() == 0.0));
+
+        // A wind stronger than friction keeps it going
+        let windy = env.with_wind(Vector::new(1.0, 0.0, 0.0));
+        let pushed = launch_with(&windy, &Projectile::new(Point::new(0.0, 0.0, 0.0), Vector::zero()), &options.with_stop(StopConditions { max_ticks: Some(100), ..StopConditions::default() }));
+        assert_eq!(pushed.termination, Termination::MaxTicks);
+        assert_approx_eq!(pushed.samples.last().unwrap().velocity().x(), 0.5, epsilon = 0.01);
+    }
+
+    #[test]
+    fn projectile_lands_without_a_resting_speed() {
+        let stop = StopConditions { resting_speed: 0.0, ..StopConditions::default() };
+        let options = LaunchOptions::default().with_stop(stop);
+        let initial = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 1.0, 0.0));
+        let trajectory = launch_with(&Environment::default(), &initial, &options);
+        assert_eq!(trajectory.termination, Termination::Landed);
+        assert_eq!(trajectory.samples, launch(0.0, 1.0, 0.0, 1.0, 1.0, 0.0));
+
+        let env = Environment::new(Vector::new(0.0, -1.0, 0.0), Vector::zero());
+        let bouncing = options.keep_velocity_magnitude().with_integrator(Integrator::VelocityVerlet).with_dt(0.01).with_ground(0.5, 0.0);
+        let trajectory = launch_with(&env, &Projectile::new(Point::new(0.0, 2.0, 0.0), Vector::zero()), &bouncing);
+        assert_eq!(trajectory.termination, Termination::Landed);
+        assert!(trajectory.samples.len() < 10_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "time step 0 must be a positive finite number")]
+    fn projectile_launch_needs_a_positive_time_step() {
+        let stop = StopConditions { max_ticks: None, max_time: Some(10.0), ..StopConditions::default() };
+        launch_with(&Environment::default(), &Projectile::default(), &LaunchOptions::default().with_dt(0.0).with_stop(stop));
+    }
+
+    #[test]
+    fn projectile_launch_rejects_invalid_steps_and_limits() {
+        let launch_in = |options: LaunchOptions| std::panic::catch_unwind(|| launch_with(&Environment::default(), &Projectile::default(), &options));
+        for dt in [-0.5, Float::NAN, Float::INFINITY] {
+            assert!(launch_in(LaunchOptions::default().with_dt(dt)).is_err(), "{}", dt);
+        }
+        for max_time in [Float::NAN, Float::INFINITY] {
+            assert!(launch_in(LaunchOptions::default().with_stop(StopConditions { max_time: Some(max_time), ..StopConditions::default() })).is_err(), "{}", max_time);
+        }
+    }
+
+    #[test]
+    fn projectile_launch_from_the_floor() {
+        let env = Environment::default();
+        let options = LaunchOptions::default().keep_velocity_magnitude();
+        let landed = launch_with(&env, &Projectile::new(Point::new(0.0, -1.0, 0.0), Vector::new(1.0, -1.0, 0.0)), &options);
+        assert_eq!((landed.samples.len(), landed.termination), (0, Termination::Landed));
+        assert!(launch(0.0, 0.0, 0.0, 1.0, 0.0, 0.0).is_empty());
+
+        let thrown = launch_with(&env, &Projectile::new(Point::new(0.0, -1.0, 0.0), Vector::new(1.0, 1.0, 0.0)), &options);
+        assert_eq!(thrown.samples[0].position(), &Point::new(1.0, 1.0, 0.0));
+        assert_eq!(thrown.termination, Termination::Landed);
+    }
+
+    #[test]
+    fn projectile_stop_conditions_give_the_reason() {
+        let floating = Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let space = Environment::new(Vector::zero(), Vector::zero());
+        let options = LaunchOptions::default().keep_velocity_magnitude();
+        let endless = launch_with(&space, &floating, &options);
+        assert_eq!((endless.samples.len(), endless.termination), (100_000, Termination::MaxTicks));
+
+        let timed = launch_with(&space, &floating, &options.with_dt(0.3).with_stop(StopConditions { max_time: Some(1.0), ..StopConditions::default() }));
+        assert_eq!(timed.termination, Termination::MaxTime);
+        assert_approx_eq!(*timed.times.last().unwrap(), 1.0);
+        assert_approx_eq!(timed.samples.last().unwrap().position().x(), 1.0);
+
+        // Long runs keep their ticks on time, a running sum would be ticks late in single precision
+        let long = launch_with(&space, &floating, &options.with_dt(0.01).with_stop(StopConditions { max_ticks: None, max_time: Some(200.0), ..StopConditions::default() }));
+        assert_eq!((long.samples.len(), long.termination), (20_000, Termination::MaxTime));
+        assert_eq!(long.times[19_999], 200.0);
+        assert_approx_eq!(long.times[9_999], 100.0, epsilon = 0.0001);
+
+        let braked = launch_with(&space.with_drag(0.5, 0.0), &floating, &options);
+        assert_eq!(braked.termination, Termination::Resting);
+        assert!(braked.samples.last().unwrap().velocity().magnitude() < 0.001);
+    }
+
+    #[test]
+    fn projectile_trajectory_exports_csv_and_json() {
+        let space = Environment::new(Vector::zero(), Vector::zero());
+        let options = LaunchOptions::default().keep_velocity_magnitude().with_dt(0.5).with_stop(StopConditions { max_ticks: Some(2), ..StopConditions::default() });
+        let trajectory = launch_with(&space, &Projectile::new(Point::new(0.0, 1.0, 0.0), Vector::new(3.0, 0.0, -4.0)), &options);
+        let mut csv = Vec::new();
+        trajectory.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "tick,time,x,y,z,vx,vy,vz,speed\n1,0.5,1.5,1,-2,3,0,-4,5\n2,1,3,1,-4,3,0,-4,5\n");
+
+        let mut json = Vec::new();
+        trajectory.write_json(&mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "[\n  {\"tick\": 1, \"time\": 0.5, \"position\": [1.5, 1, -2], \"velocity\": [3, 0, -4], \"speed\": 5},\n  {\"tick\": 2, \"time\": 1, \"position\": [3, 1, -4], \"velocity\": [3, 0, -4], \"speed\": 5}\n]\n"
+        );
+
+        let empty = Trajectory { samples: Vec::new(), times: Vec::new(), termination: Termination::Resting };
+        let mut out = Vec::new();
+        empty.write_json(&mut out).unwrap();
+        assert_eq!(out, b"[]\n");
+        assert_eq!(json_number(Float::NAN), "null");
+    }
+}
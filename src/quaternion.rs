@@ -0,0 +1,229 @@
+use std::ops::Mul;
+
+use crate::matrix::Matrix;
+use crate::approx::ApproxEq;
+use crate::ray::Tuple;
+use crate::scalar::{Float, Scalar};
+
+// w + xi + yj + zk, a unit quaternion being a rotation
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Quaternion<T = Float> {
+    w: T,
+    x: T,
+    y: T,
+    z: T,
+}
+
+impl<T: Scalar> Quaternion<T> {
+    pub fn new(w: T, x: T, y: T, z: T) -> Quaternion<T> {
+        Quaternion { w, x, y, z }
+    }
+    pub fn identity() -> Quaternion<T> {
+        Quaternion::new(T::ONE, T::ZERO, T::ZERO, T::ZERO)
+    }
+    // Rotation of `angle` radians around `axis`, which does not need to be normalized
+    pub fn from_axis_angle(axis: &Tuple<T>, angle: T) -> Quaternion<T> {
+        let axis = Tuple::vector3(axis.x(), axis.y(), axis.z()).normalize();
+        let half = angle * T::from_f64(0.5);
+        let s = half.sin();
+        Quaternion::new(half.cos(), axis.x() * s, axis.y() * s, axis.z() * s)
+    }
+    pub fn w(&self) -> T {
+        self.w
+    }
+    pub fn x(&self) -> T {
+        self.x
+    }
+    pub fn y(&self) -> T {
+        self.y
+    }
+    pub fn z(&self) -> T {
+        self.z
+    }
+    pub fn dot(&self, other: &Quaternion<T>) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    pub fn magnitude(&self) -> T {
+        self.dot(self).sqrt()
+    }
+    pub fn normalize(&self) -> Quaternion<T> {
+        let m = self.magnitude();
+        if m == T::ZERO {
+            *self
+        } else {
+            self.scale(T::ONE / m)
+        }
+    }
+    pub fn scale(&self, scalar: T) -> Quaternion<T> {
+        Quaternion::new(self.w * scalar, self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+    pub fn conjugate(&self) -> Quaternion<T> {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+    pub fn inverse(&self) -> Option<Quaternion<T>> {
+        let norm2 = self.dot(self);
+        if norm2 == T::ZERO {
+            None
+        } else {
+            Some(self.conjugate().scale(T::ONE / norm2))
+        }
+    }
+    // Hamilton product, `self.mul(other)` rotates by `other` first
+    pub fn mul(&self, other: &Quaternion<T>) -> Quaternion<T> {
+        Quaternion::new(
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        )
+    }
+    // Rotates the x, y, z part of the tuple, so points stay points and vectors stay vectors
+    pub fn rotate(&self, t: &Tuple<T>) -> Tuple<T> {
+        let q = self.normalize();
+        let p = Quaternion::new(T::ZERO, t.x(), t.y(), t.z());
+        let r = q * p * q.conjugate();
+        Tuple(r.x, r.y, r.z, t.w())
+    }
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let q = self.normalize();
+        let two = T::from_f64(2.0);
+        let (w, x, y, z) = (q.w, q.x, q.y, q.z);
+        Matrix::from_rows([
+            [T::ONE - two * (y * y + z * z), two * (x * y - w * z), two * (x * z + w * y), T::ZERO],
+            [two * (x * y + w * z), T::ONE - two * (x * x + z * z), two * (y * z - w * x), T::ZERO],
+            [two * (x * z - w * y), two * (y * z + w * x), T::ONE - two * (x * x + y * y), T::ZERO],
+            [T::ZERO, T::ZERO, T::ZERO, T::ONE],
+        ])
+    }
+    // Reads the rotation part of a 4x4 matrix, which must be a pure rotation
+    pub fn from_matrix(m: &Matrix<T>) -> Quaternion<T> {
+        let quarter = T::from_f64(0.25);
+        let trace = m.at(0, 0) + m.at(1, 1) + m.at(2, 2);
+        // Divide by the largest component to stay away from 0
+        let q = if trace > T::ZERO {
+            let s = (trace + T::ONE).sqrt() * T::from_f64(2.0);
+            Quaternion::new(quarter * s, (m.at(2, 1) - m.at(1, 2)) / s, (m.at(0, 2) - m.at(2, 0)) / s, (m.at(1, 0) - m.at(0, 1)) / s)
+        } else if m.at(0, 0) > m.at(1, 1) && m.at(0, 0) > m.at(2, 2) {
+            let s = (T::ONE + m.at(0, 0) - m.at(1, 1) - m.at(2, 2)).sqrt() * T::from_f64(2.0);
+            Quaternion::new((m.at(2, 1) - m.at(1, 2)) / s, quarter * s, (m.at(0, 1) + m.at(1, 0)) / s, (m.at(0, 2) + m.at(2, 0)) / s)
+        } else if m.at(1, 1) > m.at(2, 2) {
+            let s = (T::ONE + m.at(1, 1) - m.at(0, 0) - m.at(2, 2)).sqrt() * T::from_f64(2.0);
+            Quaternion::new((m.at(0, 2) - m.at(2, 0)) / s, (m.at(0, 1) + m.at(1, 0)) / s, quarter * s, (m.at(1, 2) + m.at(2, 1)) / s)
+        } else {
+            let s = (T::ONE + m.at(2, 2) - m.at(0, 0) - m.at(1, 1)).sqrt() * T::from_f64(2.0);
+            Quaternion::new((m.at(1, 0) - m.at(0, 1)) / s, (m.at(0, 2) + m.at(2, 0)) / s, (m.at(1, 2) + m.at(2, 1)) / s, quarter * s)
+        };
+        q.normalize()
+    }
+    // Spherical linear interpolation along the shortest arc, `t` going from 0 (self) to 1 (other)
+    pub fn slerp(&self, other: &Quaternion<T>, t: T) -> Quaternion<T> {
+        let a = self.normalize();
+        let mut b = other.normalize();
+        let mut cos_theta = a.dot(&b);
+        if cos_theta < T::ZERO {
+            b = b.scale(-T::ONE);
+            cos_theta = -cos_theta;
+        }
+        // Nearly parallel: sin(theta) is too small to divide by
+        if cos_theta > T::from_f64(0.9995) {
+            let lerp = Quaternion::new(
+                a.w + (b.w - a.w) * t,
+                a.x + (b.x - a.x) * t,
+                a.y + (b.y - a.y) * t,
+                a.z + (b.z - a.z) * t,
+            );
+            return lerp.normalize();
+        }
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        let wa = ((T::ONE - t) * theta).sin() / sin_theta;
+        let wb = (t * theta).sin() / sin_theta;
+        Quaternion::new(
+            a.w * wa + b.w * wb,
+            a.x * wa + b.x * wb,
+            a.y * wa + b.y * wb,
+            a.z * wa + b.z * wb,
+        )
+    }
+    // q and -q are the same rotation
+    pub fn same_rotation(&self, other: &Quaternion<T>) -> bool {
+        self.approx_eq(other) || self.approx_eq(&other.scale(-T::ONE))
+    }
+}
+
+impl<T: Scalar> Mul for Quaternion<T> {
+    type Output = Quaternion<T>;
+    fn mul(self, other: Quaternion<T>) -> Quaternion<T> {
+        Quaternion::mul(&self, &other)
+    }
+}
+
+impl<T: Scalar> Mul<Tuple<T>> for Quaternion<T> {
+    type Output = Tuple<T>;
+    fn mul(self, t: Tuple<T>) -> Tuple<T> {
+        self.rotate(&t)
+    }
+}
+
+#[cfg(test)]
+mod quaternion_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::transform::{rotation_x, rotation_y, rotation_z};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn quaternion_rotates_like_rotation_y() {
+        let q = Quaternion::from_axis_angle(&Tuple::vector3(0.0, 2.0, 0.0), PI / 2.0);
+        let v = Tuple::vector3(0.0, 0.0, 1.0);
+        assert_approx_eq!(q.rotate(&v), Tuple::vector3(1.0, 0.0, 0.0));
+        assert_approx_eq!(q * v, &rotation_y(PI / 2.0) * v);
+    }
+
+    #[test]
+    fn quaternion_rotation_keeps_points_as_points() {
+        let q = Quaternion::from_axis_angle(&Tuple::vector3(1.0, 0.0, 0.0), PI / 2.0);
+        assert_approx_eq!(q.rotate(&Tuple::point3(0.0, 1.0, 0.0)), Tuple::point3(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn quaternion_product_composes_rotations() {
+        let qx = Quaternion::from_axis_angle(&Tuple::vector3(1.0, 0.0, 0.0), PI / 2.0);
+        let qz = Quaternion::from_axis_angle(&Tuple::vector3(0.0, 0.0, 1.0), PI / 2.0);
+        let p = Tuple::point3(1.0, 2.0, 3.0);
+        let by_matrix = &(&rotation_z(PI / 2.0) * &rotation_x(PI / 2.0)) * p;
+        assert_approx_eq!((qz * qx).rotate(&p), by_matrix);
+    }
+
+    #[test]
+    fn quaternion_inverse_undoes_rotation() {
+        let q = Quaternion::new(1.0, 2.0, 3.0, 4.0);
+        assert_approx_eq!(q * q.inverse().unwrap(), Quaternion::identity());
+        assert_eq!(q.conjugate(), Quaternion::new(1.0, -2.0, -3.0, -4.0));
+        assert_eq!(Quaternion::new(0.0, 0.0, 0.0, 0.0).inverse(), None);
+    }
+
+    #[test]
+    fn quaternion_round_trips_through_matrix() {
+        let m = rotation_x(PI / 3.0);
+        assert_approx_eq!(Quaternion::from_axis_angle(&Tuple::vector3(1.0, 0.0, 0.0), PI / 3.0).to_matrix(), m);
+        for angle in [0.0, PI / 4.0, PI * 0.99, -PI / 2.0] {
+            for axis in [Tuple::vector3(1.0, 0.0, 0.0), Tuple::vector3(0.0, 1.0, 0.0), Tuple::vector3(1.0, -2.0, 3.0)] {
+                let q = Quaternion::from_axis_angle(&axis, angle);
+                assert!(Quaternion::from_matrix(&q.to_matrix()).same_rotation(&q), "{:?} at {}", axis, angle);
+            }
+        }
+    }
+
+    #[test]
+    fn quaternion_slerp_interpolates_angle() {
+        let axis = Tuple::vector3(0.0, 0.0, 1.0);
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(&axis, PI / 2.0);
+        assert_approx_eq!(a.slerp(&b, 0.0), a);
+        assert_approx_eq!(a.slerp(&b, 1.0), b);
+        assert_approx_eq!(a.slerp(&b, 0.5), Quaternion::from_axis_angle(&axis, PI / 4.0));
+        // -b is the same rotation, slerp still takes the short way
+        assert!(a.slerp(&b.scale(-1.0), 0.5).same_rotation(&Quaternion::from_axis_angle(&axis, PI / 4.0)));
+    }
+}
@@ -1,3 +1,7 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::scalar::{Float, Scalar};
+
 pub struct ArrayVect {
     pub elts: Vec<i32>,
 }
@@ -28,83 +32,80 @@ impl ArrayVect {
 }
 
 // A tuple struct
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Tuple(pub f32, pub f32, pub f32, pub f32);
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub struct Tuple<T = Float>(pub T, pub T, pub T, pub T);
 
-impl Tuple {
-    pub fn x(&self) -> f32 {
+impl<T: Scalar> Tuple<T> {
+    pub fn x(&self) -> T {
         self.0
     }
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> T {
         self.1
     }
-    pub fn z(&self) -> f32 {
+    pub fn z(&self) -> T {
         self.2
     }
-    pub fn w(&self) -> f32 {
+    pub fn w(&self) -> T {
         self.3
     }
     pub fn is_point(&self) -> bool {
-        self.w() == 1.0
+        self.w() == T::ONE
     }
     pub fn is_vector(&self) -> bool {
-        self.w() == 0.0
+        self.w() == T::ZERO
     }
-    pub fn point3(x: f32, y: f32, z: f32) -> Tuple {
-        Tuple(x, y, z, 1.0)
+    pub fn point3(x: T, y: T, z: T) -> Tuple<T> {
+        Tuple(x, y, z, T::ONE)
     }
-    pub fn vector3(x: f32, y: f32, z: f32) -> Tuple {
-        Tuple(x, y, z, 0.0)
+    pub fn vector3(x: T, y: T, z: T) -> Tuple<T> {
+        Tuple(x, y, z, T::ZERO)
     }
-    pub fn add(&self, other: &Tuple) -> Tuple {
+    pub fn add(&self, other: &Tuple<T>) -> Tuple<T> {
         let x = self.x() + other.x();
         let y = self.y() + other.y();
         let z = self.z() + other.z();
         let w = self.w() + other.w();
         Tuple(x, y, z, w)
     }
-    pub fn sub(&self, other: &Tuple) -> Tuple {
+    pub fn sub(&self, other: &Tuple<T>) -> Tuple<T> {
         let x = self.x() - other.x();
         let y = self.y() - other.y();
         let z = self.z() - other.z();
         let w = self.w() - other.w();
         Tuple(x, y, z, w)
     }
-    pub fn neg(&self) -> Tuple {
+    pub fn neg(&self) -> Tuple<T> {
         Tuple(-self.x(), -self.y(), -self.z(), -self.w())
     }
-    pub fn scale(&self, scalar:f32) -> Tuple {
+    pub fn scale(&self, scalar: T) -> Tuple<T> {
         let x = self.x() * scalar;
         let y = self.y() * scalar;
         let z = self.z() * scalar;
         let w = self.w() * scalar;
         Tuple(x, y, z, w)
     }
-    pub fn divide(&self, scalar:f32) -> Tuple {
-        self.scale(1.0 / scalar)
+    pub fn divide(&self, scalar: T) -> Tuple<T> {
+        self.scale(T::ONE / scalar)
     }
-    pub fn magnitude(&self) -> f32 {
-        fn square(x: f32) -> f32 {
+    pub fn magnitude(&self) -> T {
+        fn square<T: Scalar>(x: T) -> T {
             x * x
         }
         let m = square(self.x()) + square(self.y()) + square(self.z()) + square(self.w());
         m.sqrt()
     }
-    pub fn normalize(&self) -> Tuple {
+    pub fn normalize(&self) -> Tuple<T> {
         let m = self.magnitude();
-        if m == 0.0 {
+        if m == T::ZERO {
             Tuple(self.x(), self.y(), self.z(), self.w())
         } else {
             self.divide(m)
         }
     }
-    pub fn approximately(&self, other: Tuple) -> bool {
-        eqv_float(self.x(), other.x()) && eqv_float(self.y(), other.y()) && eqv_float(self.z(), other.z()) && eqv_float(self.w(), other.w())
-    }
-    pub fn dot(&self, other: &Tuple) -> f32 {
+    pub fn dot(&self, other: &Tuple<T>) -> T {
         self.x() * other.x() + self.y() * other.y() + self.z() * other.z() + self.w() * other.w()
     }
-    pub fn cross(&self, other: &Tuple) -> Tuple {
+    pub fn cross(&self, other: &Tuple<T>) -> Tuple<T> {
         let x = self.y() * other.z() - self.z() * other.y();
         let y = self.z() * other.x() - self.x() * other.z();
         let z = self.x() * other.y() - self.y() * other.x();
@@ -112,14 +113,391 @@ impl Tuple {
     }
 }
 
-pub fn eqv_float(x: f32, y: f32) -> bool {
-    let epsilon = 0.00001;
-    (x - y).abs() < epsilon
+/// A position in space. Points can be moved by a `Vector`, and the difference
+/// of two points is a `Vector`; adding two points does not compile:
+///
+/// ```compile_fail
+/// use ray_tracer::ray::Point;
+/// let p = Point::new(1.0, 2.0, 3.0).add(&Point::new(1.0, 2.0, 3.0));
+/// ```
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub struct Point<T = Float>(pub T, pub T, pub T);
+
+impl<T: Scalar> Point<T> {
+    pub fn new(x: T, y: T, z: T) -> Point<T> {
+        Point(x, y, z)
+    }
+    pub fn origin() -> Point<T> {
+        Point(T::ZERO, T::ZERO, T::ZERO)
+    }
+    pub fn x(&self) -> T {
+        self.0
+    }
+    pub fn y(&self) -> T {
+        self.1
+    }
+    pub fn z(&self) -> T {
+        self.2
+    }
+    pub fn add(&self, other: &Vector<T>) -> Point<T> {
+        Point(self.x() + other.x(), self.y() + other.y(), self.z() + other.z())
+    }
+    pub fn sub(&self, other: &Point<T>) -> Vector<T> {
+        Vector(self.x() - other.x(), self.y() - other.y(), self.z() - other.z())
+    }
+    pub fn sub_vector(&self, other: &Vector<T>) -> Point<T> {
+        Point(self.x() - other.x(), self.y() - other.y(), self.z() - other.z())
+    }
+    pub fn to_tuple(&self) -> Tuple<T> {
+        Tuple::point3(self.x(), self.y(), self.z())
+    }
+}
+
+/// A direction with a magnitude. Only vectors have a cross product:
+///
+/// ```compile_fail
+/// use ray_tracer::ray::{Point, Vector};
+/// let v = Point::new(1.0, 0.0, 0.0).cross(&Vector::new(0.0, 1.0, 0.0));
+/// ```
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub struct Vector<T = Float>(pub T, pub T, pub T);
+
+impl<T: Scalar> Vector<T> {
+    pub fn new(x: T, y: T, z: T) -> Vector<T> {
+        Vector(x, y, z)
+    }
+    pub fn zero() -> Vector<T> {
+        Vector(T::ZERO, T::ZERO, T::ZERO)
+    }
+    pub fn x(&self) -> T {
+        self.0
+    }
+    pub fn y(&self) -> T {
+        self.1
+    }
+    pub fn z(&self) -> T {
+        self.2
+    }
+    pub fn add(&self, other: &Vector<T>) -> Vector<T> {
+        Vector(self.x() + other.x(), self.y() + other.y(), self.z() + other.z())
+    }
+    pub fn sub(&self, other: &Vector<T>) -> Vector<T> {
+        Vector(self.x() - other.x(), self.y() - other.y(), self.z() - other.z())
+    }
+    pub fn neg(&self) -> Vector<T> {
+        Vector(-self.x(), -self.y(), -self.z())
+    }
+    pub fn scale(&self, scalar: T) -> Vector<T> {
+        Vector(self.x() * scalar, self.y() * scalar, self.z() * scalar)
+    }
+    pub fn divide(&self, scalar: T) -> Vector<T> {
+        self.scale(T::ONE / scalar)
+    }
+    pub fn magnitude(&self) -> T {
+        self.dot(self).sqrt()
+    }
+    pub fn normalize(&self) -> Vector<T> {
+        let m = self.magnitude();
+        if m == T::ZERO {
+            *self
+        } else {
+            self.divide(m)
+        }
+    }
+    pub fn dot(&self, other: &Vector<T>) -> T {
+        self.x() * other.x() + self.y() * other.y() + self.z() * other.z()
+    }
+    pub fn cross(&self, other: &Vector<T>) -> Vector<T> {
+        let x = self.y() * other.z() - self.z() * other.y();
+        let y = self.z() * other.x() - self.x() * other.z();
+        let z = self.x() * other.y() - self.y() * other.x();
+        Vector(x, y, z)
+    }
+    pub fn to_tuple(&self) -> Tuple<T> {
+        Tuple::vector3(self.x(), self.y(), self.z())
+    }
+}
+
+impl<T: Scalar> From<Point<T>> for Tuple<T> {
+    fn from(p: Point<T>) -> Tuple<T> {
+        p.to_tuple()
+    }
+}
+
+impl<T: Scalar> From<Vector<T>> for Tuple<T> {
+    fn from(v: Vector<T>) -> Tuple<T> {
+        v.to_tuple()
+    }
+}
+
+// The rejected tuple is handed back as the error
+impl<T: Scalar> TryFrom<Tuple<T>> for Point<T> {
+    type Error = Tuple<T>;
+    fn try_from(t: Tuple<T>) -> Result<Point<T>, Tuple<T>> {
+        if t.is_point() {
+            Ok(Point(t.x(), t.y(), t.z()))
+        } else {
+            Err(t)
+        }
+    }
+}
+
+impl<T: Scalar> TryFrom<Tuple<T>> for Vector<T> {
+    type Error = Tuple<T>;
+    fn try_from(t: Tuple<T>) -> Result<Vector<T>, Tuple<T>> {
+        if t.is_vector() {
+            Ok(Vector(t.x(), t.y(), t.z()))
+        } else {
+            Err(t)
+        }
+    }
+}
+
+// Derives the `&a op b`, `a op &b` and `&a op &b` flavours from `a op b`
+macro_rules! forward_ref_binop {
+    ($imp:ident, $method:ident, $lhs:ty, $rhs:ty, $out:ty) => {
+        impl<T: Scalar> $imp<$rhs> for &$lhs {
+            type Output = $out;
+            fn $method(self, other: $rhs) -> $out {
+                $imp::$method(*self, other)
+            }
+        }
+        impl<T: Scalar> $imp<&$rhs> for $lhs {
+            type Output = $out;
+            fn $method(self, other: &$rhs) -> $out {
+                $imp::$method(self, *other)
+            }
+        }
+        impl<T: Scalar> $imp<&$rhs> for &$lhs {
+            type Output = $out;
+            fn $method(self, other: &$rhs) -> $out {
+                $imp::$method(*self, *other)
+            }
+        }
+    };
+}
+
+// Derives `a op= &b` from `a op= b`
+macro_rules! forward_ref_op_assign {
+    ($imp:ident, $method:ident, $lhs:ty, $rhs:ty) => {
+        impl<T: Scalar> $imp<&$rhs> for $lhs {
+            fn $method(&mut self, other: &$rhs) {
+                $imp::$method(self, *other)
+            }
+        }
+    };
+}
+
+// `scalar * value` for every concrete scalar type, since `impl<T> Mul<Tuple<T>> for T`
+// is not allowed by the orphan rules
+macro_rules! scalar_mul {
+    ($scalar:ty, $($t:ident),+) => {
+        $(
+            impl Mul<$t<$scalar>> for $scalar {
+                type Output = $t<$scalar>;
+                fn mul(self, value: $t<$scalar>) -> $t<$scalar> {
+                    value.scale(self)
+                }
+            }
+            impl Mul<&$t<$scalar>> for $scalar {
+                type Output = $t<$scalar>;
+                fn mul(self, value: &$t<$scalar>) -> $t<$scalar> {
+                    value.scale(self)
+                }
+            }
+        )+
+    };
+}
+
+scalar_mul!(f32, Tuple, Vector);
+scalar_mul!(f64, Tuple, Vector);
+
+impl<T: Scalar> Add for Tuple<T> {
+    type Output = Tuple<T>;
+    fn add(self, other: Tuple<T>) -> Tuple<T> {
+        Tuple::add(&self, &other)
+    }
+}
+forward_ref_binop!(Add, add, Tuple<T>, Tuple<T>, Tuple<T>);
+
+impl<T: Scalar> Sub for Tuple<T> {
+    type Output = Tuple<T>;
+    fn sub(self, other: Tuple<T>) -> Tuple<T> {
+        Tuple::sub(&self, &other)
+    }
+}
+forward_ref_binop!(Sub, sub, Tuple<T>, Tuple<T>, Tuple<T>);
+
+impl<T: Scalar> Neg for Tuple<T> {
+    type Output = Tuple<T>;
+    fn neg(self) -> Tuple<T> {
+        Tuple::neg(&self)
+    }
+}
+
+impl<T: Scalar> Neg for &Tuple<T> {
+    type Output = Tuple<T>;
+    fn neg(self) -> Tuple<T> {
+        Tuple::neg(self)
+    }
+}
+
+impl<T: Scalar> Mul<T> for Tuple<T> {
+    type Output = Tuple<T>;
+    fn mul(self, scalar: T) -> Tuple<T> {
+        self.scale(scalar)
+    }
+}
+forward_ref_binop!(Mul, mul, Tuple<T>, T, Tuple<T>);
+
+impl<T: Scalar> Div<T> for Tuple<T> {
+    type Output = Tuple<T>;
+    fn div(self, scalar: T) -> Tuple<T> {
+        self.divide(scalar)
+    }
+}
+forward_ref_binop!(Div, div, Tuple<T>, T, Tuple<T>);
+
+impl<T: Scalar> AddAssign for Tuple<T> {
+    fn add_assign(&mut self, other: Tuple<T>) {
+        *self = Tuple::add(self, &other)
+    }
+}
+forward_ref_op_assign!(AddAssign, add_assign, Tuple<T>, Tuple<T>);
+
+impl<T: Scalar> SubAssign for Tuple<T> {
+    fn sub_assign(&mut self, other: Tuple<T>) {
+        *self = Tuple::sub(self, &other)
+    }
+}
+forward_ref_op_assign!(SubAssign, sub_assign, Tuple<T>, Tuple<T>);
+
+impl<T: Scalar> MulAssign<T> for Tuple<T> {
+    fn mul_assign(&mut self, scalar: T) {
+        *self = self.scale(scalar)
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Tuple<T> {
+    fn div_assign(&mut self, scalar: T) {
+        *self = self.divide(scalar)
+    }
+}
+
+impl<T: Scalar> Add<Vector<T>> for Point<T> {
+    type Output = Point<T>;
+    fn add(self, other: Vector<T>) -> Point<T> {
+        Point::add(&self, &other)
+    }
+}
+forward_ref_binop!(Add, add, Point<T>, Vector<T>, Point<T>);
+
+impl<T: Scalar> Sub for Point<T> {
+    type Output = Vector<T>;
+    fn sub(self, other: Point<T>) -> Vector<T> {
+        Point::sub(&self, &other)
+    }
+}
+forward_ref_binop!(Sub, sub, Point<T>, Point<T>, Vector<T>);
+
+impl<T: Scalar> Sub<Vector<T>> for Point<T> {
+    type Output = Point<T>;
+    fn sub(self, other: Vector<T>) -> Point<T> {
+        self.sub_vector(&other)
+    }
+}
+forward_ref_binop!(Sub, sub, Point<T>, Vector<T>, Point<T>);
+
+impl<T: Scalar> AddAssign<Vector<T>> for Point<T> {
+    fn add_assign(&mut self, other: Vector<T>) {
+        *self = Point::add(self, &other)
+    }
+}
+forward_ref_op_assign!(AddAssign, add_assign, Point<T>, Vector<T>);
+
+impl<T: Scalar> SubAssign<Vector<T>> for Point<T> {
+    fn sub_assign(&mut self, other: Vector<T>) {
+        *self = self.sub_vector(&other)
+    }
+}
+forward_ref_op_assign!(SubAssign, sub_assign, Point<T>, Vector<T>);
+
+impl<T: Scalar> Add for Vector<T> {
+    type Output = Vector<T>;
+    fn add(self, other: Vector<T>) -> Vector<T> {
+        Vector::add(&self, &other)
+    }
+}
+forward_ref_binop!(Add, add, Vector<T>, Vector<T>, Vector<T>);
+
+impl<T: Scalar> Sub for Vector<T> {
+    type Output = Vector<T>;
+    fn sub(self, other: Vector<T>) -> Vector<T> {
+        Vector::sub(&self, &other)
+    }
+}
+forward_ref_binop!(Sub, sub, Vector<T>, Vector<T>, Vector<T>);
+
+impl<T: Scalar> Neg for Vector<T> {
+    type Output = Vector<T>;
+    fn neg(self) -> Vector<T> {
+        Vector::neg(&self)
+    }
+}
+
+impl<T: Scalar> Neg for &Vector<T> {
+    type Output = Vector<T>;
+    fn neg(self) -> Vector<T> {
+        Vector::neg(self)
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vector<T> {
+    type Output = Vector<T>;
+    fn mul(self, scalar: T) -> Vector<T> {
+        self.scale(scalar)
+    }
+}
+forward_ref_binop!(Mul, mul, Vector<T>, T, Vector<T>);
+
+impl<T: Scalar> Div<T> for Vector<T> {
+    type Output = Vector<T>;
+    fn div(self, scalar: T) -> Vector<T> {
+        self.divide(scalar)
+    }
+}
+forward_ref_binop!(Div, div, Vector<T>, T, Vector<T>);
+
+impl<T: Scalar> AddAssign for Vector<T> {
+    fn add_assign(&mut self, other: Vector<T>) {
+        *self = Vector::add(self, &other)
+    }
+}
+forward_ref_op_assign!(AddAssign, add_assign, Vector<T>, Vector<T>);
+
+impl<T: Scalar> SubAssign for Vector<T> {
+    fn sub_assign(&mut self, other: Vector<T>) {
+        *self = Vector::sub(self, &other)
+    }
+}
+forward_ref_op_assign!(SubAssign, sub_assign, Vector<T>, Vector<T>);
+
+impl<T: Scalar> MulAssign<T> for Vector<T> {
+    fn mul_assign(&mut self, scalar: T) {
+        *self = self.scale(scalar)
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Vector<T> {
+    fn div_assign(&mut self, scalar: T) {
+        *self = self.divide(scalar)
+    }
 }
 
 #[cfg(test)]
 mod ray_tests {
     use super::*;
+    use crate::assert_approx_eq;
 
     #[test]
     fn ray_can_create_array_from_new() {
@@ -133,4 +511,95 @@ mod ray_tests {
         let b = ArrayVect::array3(3, 4, 5);
         assert_eq!(a.concat(b).elts, vec![1, 2, 3, 3, 4, 5])
     }
+
+    #[test]
+    fn ray_point_minus_point_is_vector() {
+        let v = Point::new(3.0, 2.0, 1.0).sub(&Point::new(5.0, 6.0, 7.0));
+        assert_eq!(v, Vector::new(-2.0, -4.0, -6.0));
+    }
+
+    #[test]
+    fn ray_point_moved_by_vector_is_point() {
+        let p = Point::new(3.0, 2.0, 1.0);
+        let v = Vector::new(5.0, 6.0, 7.0);
+        assert_eq!(p.add(&v), Point::new(8.0, 8.0, 8.0));
+        assert_eq!(p.sub_vector(&v), Point::new(-2.0, -4.0, -6.0));
+    }
+
+    #[test]
+    fn ray_vector_cross_product() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(2.0, 3.0, 4.0);
+        assert_eq!(a.cross(&b), Vector::new(-1.0, 2.0, -1.0));
+        assert_eq!(b.cross(&a), Vector::new(1.0, -2.0, 1.0));
+    }
+
+    #[test]
+    fn ray_point_and_vector_convert_to_tuple() {
+        assert_eq!(Tuple::from(Point::new(1.0, 2.0, 3.0)), Tuple(1.0, 2.0, 3.0, 1.0));
+        assert_eq!(Tuple::from(Vector::new(1.0, 2.0, 3.0)), Tuple(1.0, 2.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn ray_tuple_converts_only_to_matching_kind() {
+        assert_eq!(Point::try_from(Tuple::point3(1.0, 2.0, 3.0)), Ok(Point::new(1.0, 2.0, 3.0)));
+        assert_eq!(Point::try_from(Tuple::vector3(1.0, 2.0, 3.0)), Err(Tuple::vector3(1.0, 2.0, 3.0)));
+        assert_eq!(Vector::try_from(Tuple::vector3(1.0, 2.0, 3.0)), Ok(Vector::new(1.0, 2.0, 3.0)));
+        assert!(Vector::try_from(Tuple(1.0, 2.0, 3.0, 2.0)).is_err());
+    }
+
+    #[test]
+    fn ray_tuple_operators_match_methods() {
+        let a = Tuple(3.0, -2.0, 5.0, 1.0);
+        let b = Tuple(-2.0, 3.0, 1.0, 0.0);
+        let (ra, rb) = (&a, &b);
+        assert_eq!(a + b, a.add(&b));
+        assert_eq!(ra + rb, Tuple(1.0, 1.0, 6.0, 1.0));
+        assert_eq!(a - b, a.sub(&b));
+        assert_eq!(ra - b, Tuple(5.0, -5.0, 4.0, 1.0));
+        assert_eq!(-a, a.neg());
+        assert_eq!(-&a, Tuple(-3.0, 2.0, -5.0, -1.0));
+        assert_eq!(a * 3.5, a.scale(3.5));
+        assert_eq!(0.5 * &a, Tuple(1.5, -1.0, 2.5, 0.5));
+        assert_eq!(a / 2.0, a.divide(2.0));
+    }
+
+    #[test]
+    fn ray_tuple_assign_operators() {
+        let mut a = Tuple(1.0, -2.0, 3.0, -4.0);
+        a += Tuple(1.0, 1.0, 1.0, 1.0);
+        assert_eq!(a, Tuple(2.0, -1.0, 4.0, -3.0));
+        a -= &Tuple(2.0, 2.0, 2.0, 2.0);
+        assert_eq!(a, Tuple(0.0, -3.0, 2.0, -5.0));
+        a *= 2.0;
+        assert_eq!(a, Tuple(0.0, -6.0, 4.0, -10.0));
+        a /= 4.0;
+        assert_eq!(a, Tuple(0.0, -1.5, 1.0, -2.5));
+    }
+
+    #[test]
+    fn ray_point_and_vector_operators() {
+        let p = Point::new(3.0, 2.0, 1.0);
+        let v = Vector::new(5.0, 6.0, 7.0);
+        let (rp, rv) = (&p, &v);
+        assert_eq!(p + v, Point::new(8.0, 8.0, 8.0));
+        assert_eq!(rp - rv, Point::new(-2.0, -4.0, -6.0));
+        assert_eq!(p - Point::new(5.0, 6.0, 7.0), Vector::new(-2.0, -4.0, -6.0));
+        assert_eq!(-v * 2.0, Vector::new(-10.0, -12.0, -14.0));
+        assert_eq!(v / 2.0 + v, 1.5 * v);
+        let mut q = p;
+        q += v;
+        q -= &v;
+        assert_eq!(q, p);
+    }
+
+    #[test]
+    fn ray_tuple_math_runs_in_both_precisions() {
+        let v32 = Tuple::<f32>::vector3(1.0, 2.0, 3.0).normalize();
+        let v64 = Tuple::<f64>::vector3(1.0, 2.0, 3.0).normalize();
+        assert_approx_eq!(v32.magnitude(), 1.0);
+        assert_approx_eq!(v64.magnitude(), 1.0);
+        assert_approx_eq!(v64.x(), 1.0 / 14.0_f64.sqrt());
+        assert_eq!(v64.cross(&Tuple::vector3(2.0, 3.0, 4.0)).w(), 0.0);
+    }
 }
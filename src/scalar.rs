@@ -0,0 +1,128 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Default precision of the crate, `f64` when the `f64` feature is enabled.
+#[cfg(not(feature = "f64"))]
+pub type Float = f32;
+#[cfg(feature = "f64")]
+pub type Float = f64;
+
+/// Floating point number the math types are built on.
+pub trait Scalar:
+    Copy
+    + Debug
+    + Display
+    + PartialOrd
+    + FromStr
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    // Default tolerance of `ApproxEq::approx_eq`
+    const EPSILON: Self;
+
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn acos(self) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    // Number of representable values between self and other, u64::MAX when either is NaN
+    fn ulps_diff(self, other: Self) -> u64;
+}
+
+impl Scalar for f32 {
+    const ZERO: f32 = 0.0;
+    const ONE: f32 = 1.0;
+    const EPSILON: f32 = 0.00001;
+
+    fn sqrt(self) -> f32 {
+        f32::sqrt(self)
+    }
+    fn abs(self) -> f32 {
+        f32::abs(self)
+    }
+    fn sin(self) -> f32 {
+        f32::sin(self)
+    }
+    fn cos(self) -> f32 {
+        f32::cos(self)
+    }
+    fn acos(self) -> f32 {
+        f32::acos(self)
+    }
+    fn from_f64(value: f64) -> f32 {
+        value as f32
+    }
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn ulps_diff(self, other: f32) -> u64 {
+        // Maps the sign-magnitude bits onto a monotonic integer line, -0.0 and 0.0 both at 0
+        fn ordered(x: f32) -> i64 {
+            let bits = x.to_bits() as i32 as i64;
+            if bits < 0 {
+                i32::MIN as i64 - bits
+            } else {
+                bits
+            }
+        }
+        if self.is_nan() || other.is_nan() {
+            return u64::MAX;
+        }
+        (ordered(self) - ordered(other)).unsigned_abs()
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: f64 = 0.0;
+    const ONE: f64 = 1.0;
+    const EPSILON: f64 = 0.0000000001;
+
+    fn sqrt(self) -> f64 {
+        f64::sqrt(self)
+    }
+    fn abs(self) -> f64 {
+        f64::abs(self)
+    }
+    fn sin(self) -> f64 {
+        f64::sin(self)
+    }
+    fn cos(self) -> f64 {
+        f64::cos(self)
+    }
+    fn acos(self) -> f64 {
+        f64::acos(self)
+    }
+    fn from_f64(value: f64) -> f64 {
+        value
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn ulps_diff(self, other: f64) -> u64 {
+        fn ordered(x: f64) -> i128 {
+            let bits = x.to_bits() as i64 as i128;
+            if bits < 0 {
+                i64::MIN as i128 - bits
+            } else {
+                bits
+            }
+        }
+        if self.is_nan() || other.is_nan() {
+            return u64::MAX;
+        }
+        let diff = (ordered(self) - ordered(other)).unsigned_abs();
+        diff.min(u64::MAX as u128) as u64
+    }
+}
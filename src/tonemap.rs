@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::image::ImageFormat;
+use crate::scalar::Scalar;
+
+// Curve bringing linear radiance into 0..1, applied to each channel
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ToneMap {
+    LinearClamp,
+    // c / (1 + c), never reaches white
+    Reinhard,
+    // Reinhard where `white` and brighter map to 1, a white point at or below 0 makes anything above black white
+    ExtendedReinhard { white: f64 },
+    // Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl ToneMap {
+    pub fn apply<T: Scalar>(&self, c: T) -> T {
+        let x = c.to_f64().max(0.0);
+        let mapped = match *self {
+            ToneMap::LinearClamp => x,
+            ToneMap::Reinhard => x / (1.0 + x),
+            ToneMap::ExtendedReinhard { white } if white <= 0.0 => if x > 0.0 { 1.0 } else { 0.0 },
+            ToneMap::ExtendedReinhard { white } => x * (1.0 + x / (white * white)) / (1.0 + x),
+            ToneMap::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+        };
+        T::from_f64(mapped.min(1.0))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Transfer {
+    // Values are written as they are, for linear formats such as PFM
+    Linear,
+    Srgb,
+}
+
+pub fn srgb_encode<T: Scalar>(c: T) -> T {
+    let x = c.to_f64();
+    let encoded = if x <= 0.0031308 { 12.92 * x } else { 1.055 * x.powf(1.0 / 2.4) - 0.055 };
+    T::from_f64(encoded)
+}
+
+pub fn srgb_decode<T: Scalar>(c: T) -> T {
+    let x = c.to_f64();
+    let decoded = if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) };
+    T::from_f64(decoded)
+}
+
+impl<T: Scalar> Color<T> {
+    pub fn to_srgb(&self) -> Color<T> {
+        Color::new(srgb_encode(self.red()), srgb_encode(self.green()), srgb_encode(self.blue()))
+    }
+    pub fn to_linear(&self) -> Color<T> {
+        Color::new(srgb_decode(self.red()), srgb_decode(self.green()), srgb_decode(self.blue()))
+    }
+}
+
+// Stage between the linear canvas and an exporter: exposure, then tone curve, then transfer function
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PostProcess {
+    // In stops, each one doubles the radiance
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+    pub transfer: Transfer,
+}
+
+impl PostProcess {
+    // What plain exports do: clamp and write linear values
+    pub fn none() -> PostProcess {
+        PostProcess { exposure: 0.0, tone_map: ToneMap::LinearClamp, transfer: Transfer::Linear }
+    }
+    pub fn srgb(tone_map: ToneMap) -> PostProcess {
+        PostProcess { exposure: 0.0, tone_map, transfer: Transfer::Srgb }
+    }
+    pub fn with_exposure(self, exposure: f64) -> PostProcess {
+        PostProcess { exposure, ..self }
+    }
+    pub fn apply<T: Scalar>(&self, color: Color<T>) -> Color<T> {
+        let exposed = color.scale(T::from_f64(2f64.powf(self.exposure)));
+        let mapped = Color::new(self.tone_map.apply(exposed.red()), self.tone_map.apply(exposed.green()), self.tone_map.apply(exposed.blue()));
+        match self.transfer {
+            Transfer::Linear => mapped,
+            Transfer::Srgb => mapped.to_srgb(),
+        }
+    }
+}
+
+impl<T: Scalar> Canvas<T> {
+    pub fn post_processed(&self, post: &PostProcess) -> Canvas<T> {
+        self.map(|c| post.apply(c))
+    }
+    pub fn write_image_with<W: Write>(&self, format: ImageFormat, post: &PostProcess, out: W) -> io::Result<()> {
+        self.post_processed(post).write_image(format, out)
+    }
+    pub fn save_with<P: AsRef<Path>>(&self, path: P, format: ImageFormat, post: &PostProcess) -> io::Result<()> {
+        self.write_image_with(format, post, BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tonemap_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::image::BitDepth;
+
+    #[test]
+    fn tonemap_operators_bring_radiance_into_range() {
+        assert_eq!(ToneMap::LinearClamp.apply(1.7), 1.0);
+        assert_eq!(ToneMap::LinearClamp.apply(-0.3), 0.0);
+        assert_eq!(ToneMap::Reinhard.apply(1.0), 0.5);
+        assert_eq!(ToneMap::Reinhard.apply(3.0), 0.75);
+        assert_approx_eq!(ToneMap::ExtendedReinhard { white: 4.0 }.apply(4.0), 1.0);
+        assert_eq!(ToneMap::ExtendedReinhard { white: 4.0 }.apply(9.0), 1.0);
+        assert_eq!(ToneMap::ExtendedReinhard { white: 0.0 }.apply(0.0), 0.0);
+        assert_eq!(ToneMap::ExtendedReinhard { white: 0.0 }.apply(0.5), 1.0);
+        assert_eq!(ToneMap::ExtendedReinhard { white: -1.0 }.apply(0.0), 0.0);
+        assert_approx_eq!(ToneMap::Aces.apply(1.0), 2.54 / 3.16);
+        assert_eq!(ToneMap::Aces.apply(0.0), 0.0);
+        assert_eq!(ToneMap::Aces.apply(100.0), 1.0);
+    }
+
+    #[test]
+    fn tonemap_srgb_round_trips() {
+        assert_approx_eq!(srgb_encode(0.5), 0.735357, epsilon = 0.000001);
+        assert_approx_eq!(srgb_encode(0.0031308), 0.04045, epsilon = 0.000001);
+        for c in [0.0, 0.001, 0.04, 0.2, 0.5, 1.0] {
+            assert_approx_eq!(srgb_decode(srgb_encode(c)), c);
+        }
+        let c = Color::new(0.25_f64, 0.5, 1.0);
+        assert_approx_eq!(c.to_srgb().to_linear(), c);
+    }
+
+    #[test]
+    fn tonemap_is_chosen_per_export() {
+        let c: Canvas<f64> = Canvas::filled(1, 1, Color::new(0.5, 1.0, 3.0));
+        let exposed = PostProcess::none().with_exposure(1.0);
+        assert_eq!(exposed.apply(Color::new(0.25, 0.5, 0.0)), Color::new(0.5, 1.0, 0.0));
+
+        let mut plain = Vec::new();
+        c.write_image_with(ImageFormat::Ppm(BitDepth::Eight), &PostProcess::none(), &mut plain).unwrap();
+        assert_eq!(plain[plain.len() - 3..], [128, 255, 255]);
+        let mut mapped = Vec::new();
+        c.write_image_with(ImageFormat::Ppm(BitDepth::Eight), &PostProcess::srgb(ToneMap::Reinhard), &mut mapped).unwrap();
+        // Reinhard gives 1/3, 1/2 and 3/4 before sRGB encoding
+        assert_eq!(mapped[mapped.len() - 3..], [156, 188, 225]);
+    }
+}
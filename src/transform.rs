@@ -0,0 +1,131 @@
+use crate::matrix::Matrix;
+use crate::ray::Tuple;
+use crate::scalar::Scalar;
+
+pub fn translation<T: Scalar>(x: T, y: T, z: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(0, 3, x);
+    m.set(1, 3, y);
+    m.set(2, 3, z);
+    m
+}
+
+pub fn scaling<T: Scalar>(x: T, y: T, z: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(0, 0, x);
+    m.set(1, 1, y);
+    m.set(2, 2, z);
+    m
+}
+
+// Rotations are in radians, clockwise when looking down the axis toward the origin
+pub fn rotation_x<T: Scalar>(r: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(1, 1, r.cos());
+    m.set(1, 2, -r.sin());
+    m.set(2, 1, r.sin());
+    m.set(2, 2, r.cos());
+    m
+}
+
+pub fn rotation_y<T: Scalar>(r: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(0, 0, r.cos());
+    m.set(0, 2, r.sin());
+    m.set(2, 0, -r.sin());
+    m.set(2, 2, r.cos());
+    m
+}
+
+pub fn rotation_z<T: Scalar>(r: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(0, 0, r.cos());
+    m.set(0, 1, -r.sin());
+    m.set(1, 0, r.sin());
+    m.set(1, 1, r.cos());
+    m
+}
+
+// Each component moves in proportion to the two others, e.g. `xy` is x in proportion to y
+pub fn shearing<T: Scalar>(xy: T, xz: T, yx: T, yz: T, zx: T, zy: T) -> Matrix<T> {
+    let mut m = Matrix::identity();
+    m.set(0, 1, xy);
+    m.set(0, 2, xz);
+    m.set(1, 0, yx);
+    m.set(1, 2, yz);
+    m.set(2, 0, zx);
+    m.set(2, 1, zy);
+    m
+}
+
+// World-to-camera matrix for an eye at `from` looking at `to`, `up` being roughly upward
+pub fn view_transform<T: Scalar>(from: Tuple<T>, to: Tuple<T>, up: Tuple<T>) -> Matrix<T> {
+    let forward = (to - from).normalize();
+    let left = forward.cross(&up.normalize());
+    let true_up = left.cross(&forward);
+    let orientation = Matrix::from_rows([
+        [left.x(), left.y(), left.z(), T::ZERO],
+        [true_up.x(), true_up.y(), true_up.z(), T::ZERO],
+        [-forward.x(), -forward.y(), -forward.z(), T::ZERO],
+        [T::ZERO, T::ZERO, T::ZERO, T::ONE],
+    ]);
+    &orientation * &translation(-from.x(), -from.y(), -from.z())
+}
+
+// Fluent builders: each call applies its transformation after the ones already chained,
+// so `Matrix::identity().rotate_x(a).scale(..).translate(..)` rotates first
+impl<T: Scalar> Matrix<T> {
+    pub fn translate(self, x: T, y: T, z: T) -> Matrix<T> {
+        &translation(x, y, z) * &self
+    }
+    pub fn scale(self, x: T, y: T, z: T) -> Matrix<T> {
+        &scaling(x, y, z) * &self
+    }
+    pub fn rotate_x(self, r: T) -> Matrix<T> {
+        &rotation_x(r) * &self
+    }
+    pub fn rotate_y(self, r: T) -> Matrix<T> {
+        &rotation_y(r) * &self
+    }
+    pub fn rotate_z(self, r: T) -> Matrix<T> {
+        &rotation_z(r) * &self
+    }
+    pub fn shear(self, xy: T, xz: T, yx: T, yz: T, zx: T, zy: T) -> Matrix<T> {
+        &shearing(xy, xz, yx, yz, zx, zy) * &self
+    }
+}
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::ray::{Point, Tuple, Vector};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn transform_translation_ignores_vectors() {
+        let v = Tuple::vector3(-3.0, 4.0, 5.0);
+        assert_eq!(translation(5.0, -3.0, 2.0) * v, v);
+    }
+
+    #[test]
+    fn transform_fluent_chain_matches_reversed_product() {
+        let chained = Matrix::identity().rotate_x(PI / 2.0).scale(5.0, 5.0, 5.0).translate(10.0, 5.0, 7.0);
+        let product = &(&translation(10.0, 5.0, 7.0) * &scaling(5.0, 5.0, 5.0)) * &rotation_x(PI / 2.0);
+        assert_approx_eq!(chained, product);
+        let p = &chained * Point::new(1.0, 0.0, 1.0);
+        assert!((p - Point::new(15.0, 0.0, 7.0)).magnitude() < 0.00001);
+    }
+
+    #[test]
+    fn transform_view_moves_world_opposite_to_eye() {
+        let t = view_transform(Tuple::point3(0.0, 0.0, 8.0), Tuple::point3(0.0, 0.0, 0.0), Tuple::vector3(0.0, 1.0, 0.0));
+        assert_eq!(t, translation(0.0, 0.0, -8.0));
+    }
+
+    #[test]
+    fn transform_negative_scaling_reflects() {
+        assert_eq!(&scaling(-1.0, 1.0, 1.0) * Point::new(2.0, 3.0, 4.0), Point::new(-2.0, 3.0, 4.0));
+        assert_eq!(&scaling(-1.0, 1.0, 1.0) * Vector::new(2.0, 3.0, 4.0), Vector::new(-2.0, 3.0, 4.0));
+    }
+}
@@ -0,0 +1,315 @@
+// zlib stream (RFC 1950) around deflate (RFC 1951), just enough to write PNG data
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Compression {
+    // Uncompressed blocks, fastest to write
+    Stored,
+    // Fixed Huffman codes over greedy LZ77 matches
+    Deflate,
+}
+
+const MAX_STORED_BLOCK: usize = 65535;
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// Candidates tried per position, longer chains compress better but slower
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before b overflows
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+pub fn zlib_compress(data: &[u8], compression: Compression) -> Vec<u8> {
+    // Deflate with a 32K window, FCHECK makes the header a multiple of 31
+    let mut out = vec![0x78, 0x01];
+    match compression {
+        Compression::Stored => deflate_stored(data, &mut out),
+        Compression::Deflate => deflate_fixed(data, &mut out),
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn deflate_stored(data: &[u8], out: &mut Vec<u8>) {
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // An empty stream still needs one final block
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        // BFINAL then BTYPE 00, padded to the byte boundary
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+}
+
+// Packs bits least significant first, as deflate expects
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+    // Huffman codes are defined most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - bits), bits);
+    }
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+            self.buffer = 0;
+            self.count = 0;
+        }
+    }
+}
+
+// Code and length of a literal/length symbol in the fixed Huffman table
+fn fixed_literal_code(symbol: u16) -> (u32, u32) {
+    let symbol = symbol as u32;
+    match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    }
+}
+
+// Index of the last base not above value
+fn code_index(bases: &[u16], value: usize) -> usize {
+    bases.iter().rposition(|&base| base as usize <= value).unwrap()
+}
+
+fn write_match(bits: &mut BitWriter, length: usize, distance: usize) {
+    let l = code_index(&LENGTH_BASE, length);
+    let (code, len) = fixed_literal_code(257 + l as u16);
+    bits.write_code(code, len);
+    bits.write_bits((length - LENGTH_BASE[l] as usize) as u32, LENGTH_EXTRA[l] as u32);
+    let d = code_index(&DISTANCE_BASE, distance);
+    bits.write_code(d as u32, 5);
+    bits.write_bits((distance - DISTANCE_BASE[d] as usize) as u32, DISTANCE_EXTRA[d] as u32);
+}
+
+fn hash3(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+    (v.wrapping_mul(2654435761) >> 8) & ((1 << HASH_BITS) - 1)
+}
+
+fn insert_hash(data: &[u8], i: usize, head: &mut [usize], prev: &mut [usize]) {
+    if i + MIN_MATCH <= data.len() {
+        let h = hash3(data, i);
+        prev[i & (WINDOW_SIZE - 1)] = head[h];
+        head[h] = i;
+    }
+}
+
+fn deflate_fixed(data: &[u8], out: &mut Vec<u8>) {
+    let mut bits = BitWriter { out, buffer: 0, count: 0 };
+    // A single final block with fixed codes
+    bits.write_bits(1, 1);
+    bits.write_bits(1, 2);
+    // Most recent position of each hash, and the previous one with the same hash for the positions still in the window,
+    // a slot being reused once its position is too far back to be matched
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_distance) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash3(data, i)];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = data[candidate..].iter().zip(&data[i..i + max_len]).take_while(|(a, b)| a == b).count();
+                if len > best_len {
+                    best_len = len;
+                    best_distance = i - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate & (WINDOW_SIZE - 1)];
+                chain += 1;
+            }
+        }
+        if best_len >= MIN_MATCH {
+            write_match(&mut bits, best_len, best_distance);
+            for j in i..i + best_len {
+                insert_hash(data, j, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            let (code, len) = fixed_literal_code(data[i] as u16);
+            bits.write_code(code, len);
+            insert_hash(data, i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    let (code, len) = fixed_literal_code(256);
+    bits.write_code(code, len);
+    bits.flush();
+}
+
+#[cfg(test)]
+pub(crate) mod zlib_tests {
+    use super::*;
+
+    // Reads bits least significant first
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+        bit: u32,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn bits(&mut self, n: u32) -> u32 {
+            let mut v = 0;
+            for i in 0..n {
+                let b = (self.data[self.pos] >> self.bit) & 1;
+                v |= (b as u32) << i;
+                self.bit += 1;
+                if self.bit == 8 {
+                    self.bit = 0;
+                    self.pos += 1;
+                }
+            }
+            v
+        }
+        fn code(&mut self, n: u32) -> u32 {
+            (0..n).fold(0, |acc, _| (acc << 1) | self.bits(1))
+        }
+        fn fixed_symbol(&mut self) -> u16 {
+            let c7 = self.code(7);
+            if c7 <= 0x17 {
+                return (c7 + 256) as u16;
+            }
+            let c8 = (c7 << 1) | self.bits(1);
+            match c8 {
+                0x30..=0xbf => (c8 - 0x30) as u16,
+                0xc0..=0xc7 => (c8 - 0xc0 + 280) as u16,
+                _ => (((c8 << 1) | self.bits(1)) - 0x190 + 144) as u16,
+            }
+        }
+    }
+
+    // Inflates stored and fixed Huffman blocks, enough to check our own output
+    pub(crate) fn zlib_decompress(stream: &[u8]) -> Vec<u8> {
+        assert_eq!((stream[0] as u16 * 256 + stream[1] as u16) % 31, 0);
+        let mut r = BitReader { data: &stream[2..], pos: 0, bit: 0 };
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let last = r.bits(1);
+            match r.bits(2) {
+                0 => {
+                    if r.bit > 0 {
+                        r.bit = 0;
+                        r.pos += 1;
+                    }
+                    let len = u16::from_le_bytes([r.data[r.pos], r.data[r.pos + 1]]) as usize;
+                    assert_eq!(!len as u16, u16::from_le_bytes([r.data[r.pos + 2], r.data[r.pos + 3]]));
+                    out.extend_from_slice(&r.data[r.pos + 4..r.pos + 4 + len]);
+                    r.pos += 4 + len;
+                }
+                1 => loop {
+                    let symbol = r.fixed_symbol();
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                    } else if symbol == 256 {
+                        break;
+                    } else {
+                        let l = (symbol - 257) as usize;
+                        let length = LENGTH_BASE[l] as usize + r.bits(LENGTH_EXTRA[l] as u32) as usize;
+                        let d = r.code(5) as usize;
+                        let distance = DISTANCE_BASE[d] as usize + r.bits(DISTANCE_EXTRA[d] as u32) as usize;
+                        for _ in 0..length {
+                            out.push(out[out.len() - distance]);
+                        }
+                    }
+                },
+                t => panic!("unsupported block type {}", t),
+            }
+            if last == 1 {
+                break;
+            }
+        }
+        if r.bit > 0 {
+            r.pos += 1;
+        }
+        let checksum = u32::from_be_bytes(r.data[r.pos..r.pos + 4].try_into().unwrap());
+        assert_eq!(checksum, adler32(&out));
+        out
+    }
+
+    #[test]
+    fn zlib_adler32_matches_reference() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&[]), 1);
+        assert_eq!(adler32(&vec![0xff; 100_000]), 0x149a_302c);
+    }
+
+    #[test]
+    fn zlib_stored_stream_is_byte_exact() {
+        assert_eq!(zlib_compress(b"abc", Compression::Stored), [0x78, 0x01, 0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27]);
+        assert_eq!(zlib_decompress(&zlib_compress(&[], Compression::Stored)), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn zlib_round_trips_both_modes() {
+        let mut data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8 ^ (i / 997) as u8).collect();
+        data.extend(std::iter::repeat_n(7, 1000));
+        for compression in [Compression::Stored, Compression::Deflate] {
+            assert_eq!(zlib_decompress(&zlib_compress(&data, compression)), data);
+        }
+        let deflated = zlib_compress(&data, Compression::Deflate);
+        assert!(deflated.len() < data.len() / 4, "{} bytes", deflated.len());
+        assert_eq!(zlib_decompress(&zlib_compress(b"a", Compression::Deflate)), b"a");
+    }
+
+    #[test]
+    fn zlib_matches_reach_back_a_whole_window() {
+        let noise = |period: usize| -> Vec<u8> {
+            let mut state = 1u32;
+            let block: Vec<u8> = (0..period).map(|_| { state = state.wrapping_mul(1664525).wrapping_add(1013904223); (state >> 24) as u8 }).collect();
+            block.iter().cycle().take(3 * period).copied().collect()
+        };
+        // Repeats exactly one window back are found, one byte further they are out of reach
+        let within = noise(WINDOW_SIZE);
+        let deflated = zlib_compress(&within, Compression::Deflate);
+        assert_eq!(zlib_decompress(&deflated), within);
+        assert!(deflated.len() < within.len() / 2, "{} bytes", deflated.len());
+        let beyond = noise(WINDOW_SIZE + 1);
+        let deflated = zlib_compress(&beyond, Compression::Deflate);
+        assert_eq!(zlib_decompress(&deflated), beyond);
+        assert!(deflated.len() > beyond.len() / 2, "{} bytes", deflated.len());
+    }
+}
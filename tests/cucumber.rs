@@ -1,6 +1,8 @@
-use cucumber::{after, before, cucumber, steps};
-#[path = "../src/ray.rs"]
-mod ray;
+use cucumber::{after, before, cucumber};
+use ray_tracer::canvas::Canvas;
+use ray_tracer::color::Color;
+use ray_tracer::matrix::Matrix;
+use ray_tracer::ray;
 use std::collections::HashMap;
 
 pub struct MyWorld {
@@ -8,6 +10,12 @@ pub struct MyWorld {
     foo: String,
     env_array: HashMap<String, ray::ArrayVect>,
     env_tuple: HashMap<String, ray::Tuple>,
+    env_matrix: HashMap<String, Matrix>,
+    env_color: HashMap<String, Color>,
+    env_canvas: HashMap<String, Canvas>,
+    env_text: HashMap<String, String>,
+    // Scene rendered by the last `Given the scene ...` step, kept in `env_canvas` under its name
+    rendered_scene: Option<String>,
 }
 
 impl MyWorld {
@@ -26,6 +34,47 @@ impl MyWorld {
     fn read_from_env_tuple(&self, var_name: String) -> std::option::Option<&ray::Tuple> {
         self.env_tuple.get(&var_name)
     }
+
+    fn add_to_env_matrix(&mut self, var_name: String, var_value: Matrix) {
+        self.env_matrix.insert(var_name, var_value);
+    }
+
+    // `identity_matrix` is always defined
+    fn read_from_env_matrix(&self, var_name: String) -> std::option::Option<Matrix> {
+        if var_name == "identity_matrix" {
+            Some(Matrix::identity())
+        } else {
+            self.env_matrix.get(&var_name).cloned()
+        }
+    }
+
+    fn add_to_env_color(&mut self, var_name: String, var_value: Color) {
+        self.env_color.insert(var_name, var_value);
+    }
+
+    fn read_from_env_color(&self, var_name: String) -> std::option::Option<&Color> {
+        self.env_color.get(&var_name)
+    }
+
+    fn add_to_env_canvas(&mut self, var_name: String, var_value: Canvas) {
+        self.env_canvas.insert(var_name, var_value);
+    }
+
+    fn read_from_env_canvas(&self, var_name: String) -> std::option::Option<&Canvas> {
+        self.env_canvas.get(&var_name)
+    }
+
+    fn read_from_env_canvas_mut(&mut self, var_name: String) -> std::option::Option<&mut Canvas> {
+        self.env_canvas.get_mut(&var_name)
+    }
+
+    fn add_to_env_text(&mut self, var_name: String, var_value: String) {
+        self.env_text.insert(var_name, var_value);
+    }
+
+    fn read_from_env_text(&self, var_name: String) -> std::option::Option<&String> {
+        self.env_text.get(&var_name)
+    }
 }
 
 impl cucumber::World for MyWorld {}
@@ -36,18 +85,94 @@ impl std::default::Default for MyWorld {
             foo: "a default string".to_string(),
             env_array: HashMap::new(),
             env_tuple: HashMap::new(),
+            env_matrix: HashMap::new(),
+            env_color: HashMap::new(),
+            env_canvas: HashMap::new(),
+            env_text: HashMap::new(),
+            rendered_scene: None,
         }
     }
 }
 
-mod example_steps {
-    use super::*;
-    use cucumber::steps;
+// Parsing and comparison shared by the step modules
+mod step_helpers {
+    use ray_tracer::assert_approx_eq;
+    use ray_tracer::matrix::Matrix;
+    use ray_tracer::transform;
+    use ray_tracer::Float;
+
+    // Literals in the features are only given to 5 decimals
+    pub const FEATURE_EPSILON: Float = 0.00001;
 
-    fn float_value_from(sign: String, value: i32, dec: i32) -> f32 {
+    pub fn float_value_from(sign: String, value: i32, dec: i32) -> Float {
         format!("{}{}.{}", sign, value, dec).parse().unwrap()
     }
 
+    // Accepts plain numbers as well as `pi`, `pi / 4` or `-pi / 2`
+    pub fn float_from_expr(expr: &str) -> Float {
+        let expr = expr.trim();
+        let (sign, expr) = match expr.strip_prefix('-') {
+            Some(rest) => (-1.0, rest.trim()),
+            None => (1.0, expr),
+        };
+        let value: Float = if let Some(rest) = expr.strip_prefix("pi") {
+            match rest.trim().strip_prefix('/') {
+                Some(divisor) => std::f64::consts::PI as Float / divisor.trim().parse::<Float>().unwrap(),
+                None => std::f64::consts::PI as Float,
+            }
+        } else {
+            expr.parse().unwrap()
+        };
+        sign * value
+    }
+
+    pub fn floats_from_list(list: &str) -> Vec<Float> {
+        list.split(',').map(float_from_expr).collect()
+    }
+
+    pub fn transformation_from(name: &str, args: &[Float]) -> Matrix {
+        match (name, args) {
+            ("translation", [x, y, z]) => transform::translation(*x, *y, *z),
+            ("scaling", [x, y, z]) => transform::scaling(*x, *y, *z),
+            ("rotation_x", [r]) => transform::rotation_x(*r),
+            ("rotation_y", [r]) => transform::rotation_y(*r),
+            ("rotation_z", [r]) => transform::rotation_z(*r),
+            ("shearing", [xy, xz, yx, yz, zx, zy]) => transform::shearing(*xy, *xz, *yx, *yz, *zx, *zy),
+            _ => panic!("unknown transformation {}{:?}", name, args),
+        }
+    }
+
+    pub fn apply_fluent(m: Matrix, name: &str, args: &[Float]) -> Matrix {
+        match (name, args) {
+            ("translate", [x, y, z]) => m.translate(*x, *y, *z),
+            ("scale", [x, y, z]) => m.scale(*x, *y, *z),
+            ("rotate_x", [r]) => m.rotate_x(*r),
+            ("rotate_y", [r]) => m.rotate_y(*r),
+            ("rotate_z", [r]) => m.rotate_z(*r),
+            ("shear", [xy, xz, yx, yz, zx, zy]) => m.shear(*xy, *xz, *yx, *yz, *zx, *zy),
+            _ => panic!("unknown fluent transformation {}{:?}", name, args),
+        }
+    }
+
+    // The header of a gherkin table is its first row
+    pub fn matrix_from_table(size: usize, table: &cucumber::gherkin::Table) -> Matrix {
+        let elts: Vec<Float> = std::iter::once(&table.header)
+            .chain(table.rows.iter())
+            .flat_map(|row| row.iter().map(|cell| cell.trim().parse::<Float>().unwrap()))
+            .collect();
+        Matrix::new(size, elts)
+    }
+
+    pub fn assert_matrix_within(expected: &Matrix, actual: &Matrix, epsilon: Float) {
+        assert_approx_eq!(*actual, *expected, epsilon = epsilon);
+    }
+}
+
+mod example_steps {
+    use super::step_helpers::*;
+    use ray_tracer::assert_approx_eq;
+    use cucumber::steps;
+
     // Any type that implements cucumber::World + Default can be the world
     steps!(crate::MyWorld => {
         given "I am trying out Cucumber" |world, _step| {
@@ -255,7 +380,7 @@ mod example_steps {
             let a = float_value_from(String::from(""), variable_x_value, variable_x_dec_value);
             let r1 = world.read_from_env_tuple(variable_name).unwrap();
             let r = r1.magnitude();
-            assert!(a == r || super::ray::eqv_float(a, r));
+            assert_approx_eq!(r, a);
         };
 
         then regex r"^magnitude (.+) == sqrt (\d+).(\d+)" (String, i32, i32) |world, variable_name, variable_x_value, variable_x_dec_value, _step| {
@@ -281,8 +406,7 @@ mod example_steps {
             let a = super::ray::Tuple::vector3(float_value_from(variable_x_sign, variable_x_value, variable_x_dec_value), float_value_from(variable_y_sign, variable_y_value, variable_y_dec_value), float_value_from(variable_z_sign, variable_z_value, variable_z_dec_value));
             let r1 = world.read_from_env_tuple(variable_name).unwrap();
             let r = r1.normalize();
-            let approx = r.approximately(a);
-            assert!(approx);
+            assert_approx_eq!(r, a, epsilon = FEATURE_EPSILON);
         };
 
         when regex r"^(.+) <- normalize (.+)" (String, String) | world, variable_name, variable_to_normalize, _step | {
@@ -297,7 +421,7 @@ mod example_steps {
             let r1 = world.read_from_env_tuple(variable_name).unwrap();
             let r2 = world.read_from_env_tuple(variable_name2).unwrap();
             let r = r1.dot(r2);
-            assert!(a == r || super::ray::eqv_float(a, r));
+            assert_approx_eq!(r, a);
         };
 
         then regex r"^cross (.+), (.+) == vector (-?)(\d+).(\d+), (-?)(\d+).(\d+), (-?)(\d+).(\d+)" (String, String, String, i32, i32, String, i32, i32, String, i32, i32) |world, variable_name, variable_name2, variable_x_sign, variable_x_value, variable_x_dec_value, variable_y_sign, variable_y_value, variable_y_dec_value, variable_z_sign, variable_z_value, variable_z_dec_value, _step| {
@@ -315,6 +439,494 @@ mod example_steps {
     });
 }
 
+mod matrix_steps {
+    use super::step_helpers::*;
+    use ray_tracer::approx::ApproxEq;
+    use ray_tracer::assert_approx_eq;
+    use ray_tracer::matrix::Matrix;
+    use ray_tracer::transform;
+    use cucumber::steps;
+
+    steps!(crate::MyWorld => {
+        given regex r"^the following (\d+)x(\d+) matrix (.+):$" (usize, usize, String) |world, size, _cols, variable_name, step| {
+            let m = matrix_from_table(size, step.table().unwrap());
+            world.add_to_env_matrix(variable_name, m);
+        };
+
+        then regex r"^(\w+)\[(\d+),(\d+)\] == (-?)(\d+).(\d+)$" (String, usize, usize, String, i32, i32) |world, variable_name, row, col, sign, value, dec, _step| {
+            let a = float_value_from(sign, value, dec);
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_approx_eq!(m.at(row, col), a, epsilon = FEATURE_EPSILON);
+        };
+
+        then regex r"^(\w+) == (\w+)$" (String, String) |world, variable_name, variable_name2, _step| {
+            let a = world.read_from_env_matrix(variable_name).unwrap();
+            let b = world.read_from_env_matrix(variable_name2).unwrap();
+            assert!(a.approx_eq(&b));
+        };
+
+        then regex r"^(\w+) != (\w+)$" (String, String) |world, variable_name, variable_name2, _step| {
+            let a = world.read_from_env_matrix(variable_name).unwrap();
+            let b = world.read_from_env_matrix(variable_name2).unwrap();
+            assert!(!a.approx_eq(&b));
+        };
+
+        then regex r"^(\w+) \* (\w+) == the following (\d+)x(\d+) matrix:$" (String, String, usize, usize) |world, variable_name, variable_name2, size, _cols, step| {
+            let expected = matrix_from_table(size, step.table().unwrap());
+            let a = world.read_from_env_matrix(variable_name).unwrap();
+            let b = world.read_from_env_matrix(variable_name2).unwrap();
+            assert_eq!(expected, &a * &b);
+        };
+
+        then regex r"^(\w+) \* (\w+) == tuple (-?)(\d+).(\d+), (-?)(\d+).(\d+), (-?)(\d+).(\d+), (-?)(\d+).(\d+)$" (String, String, String, i32, i32, String, i32, i32, String, i32, i32, String, i32, i32) |world, variable_name, variable_name2, variable_x_sign, variable_x_value, variable_x_dec_value, variable_y_sign, variable_y_value, variable_y_dec_value, variable_z_sign, variable_z_value, variable_z_dec_value, variable_w_sign, variable_w_value, variable_w_dec_value, _step| {
+            let a = super::ray::Tuple(float_value_from(variable_x_sign, variable_x_value, variable_x_dec_value), float_value_from(variable_y_sign, variable_y_value, variable_y_dec_value), float_value_from(variable_z_sign, variable_z_value, variable_z_dec_value), float_value_from(variable_w_sign, variable_w_value, variable_w_dec_value));
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            let t = world.read_from_env_tuple(variable_name2).unwrap();
+            assert_eq!(a, m.mul_tuple(t));
+        };
+
+        then regex r"^(\w+) \* (\w+) == (\w+)$" (String, String, String) |world, variable_name, variable_name2, variable_name3, _step| {
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            if let Some(t) = world.read_from_env_tuple(variable_name2.clone()) {
+                let expected = world.read_from_env_tuple(variable_name3).unwrap();
+                assert_eq!(*expected, m.mul_tuple(t));
+            } else {
+                let b = world.read_from_env_matrix(variable_name2).unwrap();
+                let expected = world.read_from_env_matrix(variable_name3).unwrap();
+                assert_approx_eq!(&m * &b, expected);
+            }
+        };
+
+        then regex r"^(\w+) \* inverse\((\w+)\) == (\w+)$" (String, String, String) |world, variable_name, variable_name2, variable_name3, _step| {
+            let a = world.read_from_env_matrix(variable_name).unwrap();
+            let b = world.read_from_env_matrix(variable_name2).unwrap();
+            let expected = world.read_from_env_matrix(variable_name3).unwrap();
+            assert_matrix_within(&expected, &(&a * &b.inverse().unwrap()), FEATURE_EPSILON);
+        };
+
+        then regex r"^transpose\((\w+)\) == the following (\d+)x(\d+) matrix:$" (String, usize, usize) |world, variable_name, size, _cols, step| {
+            let expected = matrix_from_table(size, step.table().unwrap());
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_eq!(expected, m.transpose());
+        };
+
+        given regex r"^(\w+) <- transpose\((\w+)\)$" (String, String) |world, variable_name, variable_name2, _step| {
+            let m = world.read_from_env_matrix(variable_name2).unwrap();
+            world.add_to_env_matrix(variable_name, m.transpose());
+        };
+
+        then regex r"^determinant\((\w+)\) == (-?)(\d+).(\d+)$" (String, String, i32, i32) |world, variable_name, sign, value, dec, _step| {
+            let a = float_value_from(sign, value, dec);
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_eq!(a, m.determinant());
+        };
+
+        then regex r"^submatrix\((\w+), (\d+), (\d+)\) == the following (\d+)x(\d+) matrix:$" (String, usize, usize, usize, usize) |world, variable_name, row, col, size, _cols, step| {
+            let expected = matrix_from_table(size, step.table().unwrap());
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_eq!(expected, m.submatrix(row, col));
+        };
+
+        given regex r"^(\w+) <- submatrix\((\w+), (\d+), (\d+)\)$" (String, String, usize, usize) |world, variable_name, variable_name2, row, col, _step| {
+            let m = world.read_from_env_matrix(variable_name2).unwrap();
+            world.add_to_env_matrix(variable_name, m.submatrix(row, col));
+        };
+
+        then regex r"^minor\((\w+), (\d+), (\d+)\) == (-?)(\d+).(\d+)$" (String, usize, usize, String, i32, i32) |world, variable_name, row, col, sign, value, dec, _step| {
+            let a = float_value_from(sign, value, dec);
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_eq!(a, m.minor(row, col));
+        };
+
+        then regex r"^cofactor\((\w+), (\d+), (\d+)\) == (-?)(\d+).(\d+)$" (String, usize, usize, String, i32, i32) |world, variable_name, row, col, sign, value, dec, _step| {
+            let a = float_value_from(sign, value, dec);
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_eq!(a, m.cofactor(row, col));
+        };
+
+        then regex r"^(\w+) is invertible$" (String) |world, variable_name, _step| {
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert!(m.is_invertible());
+        };
+
+        then regex r"^(\w+) is not invertible$" (String) |world, variable_name, _step| {
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert!(!m.is_invertible());
+        };
+
+        given regex r"^(\w+) <- inverse\((\w+)\)$" (String, String) |world, variable_name, variable_name2, _step| {
+            let m = world.read_from_env_matrix(variable_name2).unwrap();
+            world.add_to_env_matrix(variable_name, m.inverse().unwrap());
+        };
+
+        given regex r"^(\w+) <- (\w+) \* (\w+)$" (String, String, String) |world, variable_name, variable_name2, variable_name3, _step| {
+            let a = world.read_from_env_matrix(variable_name2).unwrap();
+            let b = world.read_from_env_matrix(variable_name3).unwrap();
+            world.add_to_env_matrix(variable_name, &a * &b);
+        };
+
+        then regex r"^(\w+) == approximately the following (\d+)x(\d+) matrix:$" (String, usize, usize) |world, variable_name, size, _cols, step| {
+            let expected = matrix_from_table(size, step.table().unwrap());
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_matrix_within(&expected, &m, FEATURE_EPSILON);
+        };
+
+        given regex r"^(\w+) <- (translation|scaling|rotation_x|rotation_y|rotation_z|shearing)\(([^)]*)\)$" (String, String, String) |world, variable_name, name, args, _step| {
+            let m = transformation_from(&name, &floats_from_list(&args));
+            world.add_to_env_matrix(variable_name, m);
+        };
+
+        when regex r"^(\w+) <- identity_matrix((?:\.\w+\([^)]*\))+)$" (String, String) |world, variable_name, chain, _step| {
+            let m = chain
+                .trim_start_matches('.')
+                .trim_end_matches(')')
+                .split(").")
+                .fold(Matrix::identity(), |m, call| {
+                    let (name, args) = call.split_once('(').unwrap();
+                    apply_fluent(m, name, &floats_from_list(args))
+                });
+            world.add_to_env_matrix(variable_name, m);
+        };
+
+        then regex r"^(\w+) \* (\w+) == (point|vector) (-?)(\d+).(\d+), (-?)(\d+).(\d+), (-?)(\d+).(\d+)$" (String, String, String, String, i32, i32, String, i32, i32, String, i32, i32) |world, variable_name, variable_name2, kind, variable_x_sign, variable_x_value, variable_x_dec_value, variable_y_sign, variable_y_value, variable_y_dec_value, variable_z_sign, variable_z_value, variable_z_dec_value, _step| {
+            let x = float_value_from(variable_x_sign, variable_x_value, variable_x_dec_value);
+            let y = float_value_from(variable_y_sign, variable_y_value, variable_y_dec_value);
+            let z = float_value_from(variable_z_sign, variable_z_value, variable_z_dec_value);
+            let a = if kind == "point" { super::ray::Tuple::point3(x, y, z) } else { super::ray::Tuple::vector3(x, y, z) };
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            let t = world.read_from_env_tuple(variable_name2).unwrap();
+            let r = m.mul_tuple(t);
+            assert_approx_eq!(r, a, epsilon = FEATURE_EPSILON);
+            assert_eq!(a.w(), r.w());
+        };
+
+        when regex r"^(\w+) <- (\w+) \* (\w+)$" (String, String, String) |world, variable_name, variable_name2, variable_name3, _step| {
+            let m = world.read_from_env_matrix(variable_name2).unwrap();
+            let t = world.read_from_env_tuple(variable_name3).unwrap();
+            let r = m.mul_tuple(t);
+            world.add_to_env_tuple(variable_name, r);
+        };
+
+        when regex r"^(\w+) <- (\w+) \* (\w+) \* (\w+)$" (String, String, String, String) |world, variable_name, variable_name2, variable_name3, variable_name4, _step| {
+            let a = world.read_from_env_matrix(variable_name2).unwrap();
+            let b = world.read_from_env_matrix(variable_name3).unwrap();
+            let c = world.read_from_env_matrix(variable_name4).unwrap();
+            world.add_to_env_matrix(variable_name, &(&a * &b) * &c);
+        };
+
+        then regex r"^(\w+) == (point|vector) (-?)(\d+).(\d+), (-?)(\d+).(\d+), (-?)(\d+).(\d+)$" (String, String, String, i32, i32, String, i32, i32, String, i32, i32) |world, variable_name, kind, variable_x_sign, variable_x_value, variable_x_dec_value, variable_y_sign, variable_y_value, variable_y_dec_value, variable_z_sign, variable_z_value, variable_z_dec_value, _step| {
+            let x = float_value_from(variable_x_sign, variable_x_value, variable_x_dec_value);
+            let y = float_value_from(variable_y_sign, variable_y_value, variable_y_dec_value);
+            let z = float_value_from(variable_z_sign, variable_z_value, variable_z_dec_value);
+            let a = if kind == "point" { super::ray::Tuple::point3(x, y, z) } else { super::ray::Tuple::vector3(x, y, z) };
+            let r = world.read_from_env_tuple(variable_name).unwrap();
+            assert_approx_eq!(*r, a, epsilon = FEATURE_EPSILON);
+            assert_eq!(a.w(), r.w());
+        };
+
+        when regex r"^(\w+) <- view_transform\((\w+), (\w+), (\w+)\)$" (String, String, String, String) |world, variable_name, from, to, up, _step| {
+            let from = *world.read_from_env_tuple(from).unwrap();
+            let to = *world.read_from_env_tuple(to).unwrap();
+            let up = *world.read_from_env_tuple(up).unwrap();
+            world.add_to_env_matrix(variable_name, transform::view_transform(from, to, up));
+        };
+
+        then regex r"^(\w+) == (translation|scaling|rotation_x|rotation_y|rotation_z|shearing)\(([^)]*)\)$" (String, String, String) |world, variable_name, name, args, _step| {
+            let expected = transformation_from(&name, &floats_from_list(&args));
+            let m = world.read_from_env_matrix(variable_name).unwrap();
+            assert_matrix_within(&expected, &m, FEATURE_EPSILON);
+        };
+
+    });
+}
+
+mod color_steps {
+    use ray_tracer::assert_approx_eq;
+    use ray_tracer::color::Color;
+    use ray_tracer::Float;
+    use cucumber::steps;
+
+    // Numbers are captured whole so that decimals such as 0.04 keep their leading zeros
+    steps!(crate::MyWorld => {
+        given regex r"^(\w+) <- color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, Float, Float, Float) |world, variable_name, red, green, blue, _step| {
+            world.add_to_env_color(variable_name, Color::new(red, green, blue));
+        };
+
+        then regex r"^(\w+)\.(red|green|blue) == (-?\d+\.\d+)$" (String, String, Float) |world, variable_name, channel, a, _step| {
+            let c = world.read_from_env_color(variable_name).unwrap();
+            let r = match channel.as_str() {
+                "red" => c.red(),
+                "green" => c.green(),
+                _ => c.blue(),
+            };
+            assert_eq!(a, r);
+        };
+
+        then regex r"^(\w+) (\+|-|\*) (\w+) == color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, String, String, Float, Float, Float) |world, variable_name, op, variable_name2, red, green, blue, _step| {
+            let a = Color::new(red, green, blue);
+            let c1 = *world.read_from_env_color(variable_name).unwrap();
+            let c2 = *world.read_from_env_color(variable_name2).unwrap();
+            let r = match op.as_str() {
+                "+" => c1 + c2,
+                "-" => c1 - c2,
+                _ => c1 * c2,
+            };
+            assert_approx_eq!(r, a);
+        };
+
+        then regex r"^(\w+) \* (-?\d+\.\d+) == color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, Float, Float, Float, Float) |world, variable_name, scale, red, green, blue, _step| {
+            let a = Color::new(red, green, blue);
+            let c = world.read_from_env_color(variable_name).unwrap();
+            assert_approx_eq!(c.scale(scale), a);
+        };
+
+        then regex r"^rgb8 (\w+) == (\d+), (\d+), (\d+)$" (String, u8, u8, u8) |world, variable_name, red, green, blue, _step| {
+            let c = world.read_from_env_color(variable_name).unwrap();
+            assert_eq!([red, green, blue], c.to_rgb8());
+        };
+
+        then regex r"^rgb16 (\w+) == (\d+), (\d+), (\d+)$" (String, u16, u16, u16) |world, variable_name, red, green, blue, _step| {
+            let c = world.read_from_env_color(variable_name).unwrap();
+            assert_eq!([red, green, blue], c.to_rgb16());
+        };
+    });
+}
+
+mod canvas_steps {
+    use super::step_helpers::FEATURE_EPSILON;
+    use ray_tracer::assert_approx_eq;
+    use ray_tracer::canvas::{Canvas, CanvasError};
+    use ray_tracer::color::Color;
+    use ray_tracer::ppm::PpmError;
+    use ray_tracer::Float;
+    use cucumber::steps;
+
+    steps!(crate::MyWorld => {
+        given regex r"^(\w+) <- canvas\((\d+), (\d+)\)$" (String, usize, usize) |world, variable_name, width, height, _step| {
+            world.add_to_env_canvas(variable_name, Canvas::new(width, height));
+        };
+
+        when regex r"^write_pixel\((\w+), (\d+), (\d+), (\w+)\)$" (String, usize, usize, String) |world, variable_name, x, y, color_name, _step| {
+            let color = *world.read_from_env_color(color_name).unwrap();
+            world.read_from_env_canvas_mut(variable_name).unwrap().write_pixel(x, y, color).unwrap();
+        };
+
+        when regex r"^fill\((\w+), (\w+)\)$" (String, String) |world, variable_name, color_name, _step| {
+            let color = *world.read_from_env_color(color_name).unwrap();
+            world.read_from_env_canvas_mut(variable_name).unwrap().fill(color);
+        };
+
+        when regex r"^every pixel of (\w+) is set to color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, Float, Float, Float) |world, variable_name, red, green, blue, _step| {
+            world.read_from_env_canvas_mut(variable_name).unwrap().fill(Color::new(red, green, blue));
+        };
+
+        when regex r"^(\w+) <- canvas_to_ppm\((\w+)\)$" (String, String) |world, variable_name, canvas_name, _step| {
+            let ppm = world.read_from_env_canvas(canvas_name).unwrap().to_ppm();
+            world.add_to_env_text(variable_name, ppm);
+        };
+
+        given regex r"^(\w+) <- a file containing:$" (String) |world, variable_name, step| {
+            world.add_to_env_text(variable_name, format!("{}\n", step.docstring().unwrap()));
+        };
+
+        when regex r"^(\w+) <- canvas_from_ppm\((\w+)\)$" (String, String) |world, variable_name, text_name, _step| {
+            let c = Canvas::from_ppm(world.read_from_env_text(text_name).unwrap().as_bytes()).unwrap();
+            world.add_to_env_canvas(variable_name, c);
+        };
+
+        then regex r"^(\w+)\.(width|height) == (\d+)$" (String, String, usize) |world, variable_name, dimension, a, _step| {
+            let c = world.read_from_env_canvas(variable_name).unwrap();
+            let r = if dimension == "width" { c.width() } else { c.height() };
+            assert_eq!(a, r);
+        };
+
+        then regex r"^every pixel of (\w+) is color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, Float, Float, Float) |world, variable_name, red, green, blue, _step| {
+            let a = Color::new(red, green, blue);
+            let c = world.read_from_env_canvas(variable_name).unwrap();
+            assert!(c.pixels().all(|(_, _, p)| *p == a));
+        };
+
+        then regex r"^pixel_at\((\w+), (\d+), (\d+)\) == (\w+)$" (String, usize, usize, String) |world, variable_name, x, y, color_name, _step| {
+            let a = *world.read_from_env_color(color_name).unwrap();
+            let c = world.read_from_env_canvas(variable_name).unwrap();
+            assert_eq!(Ok(a), c.pixel_at(x, y));
+        };
+
+        then regex r"^pixel_at\((\w+), (\d+), (\d+)\) == color (-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)$" (String, usize, usize, Float, Float, Float) |world, variable_name, x, y, red, green, blue, _step| {
+            let c = world.read_from_env_canvas(variable_name).unwrap();
+            assert_approx_eq!(c.pixel_at(x, y).unwrap(), Color::new(red, green, blue), epsilon = FEATURE_EPSILON);
+        };
+
+        then regex r"^canvas_from_ppm\((\w+)\) fails with (a bad magic number|truncated data|a value out of range)$" (String, String) |world, text_name, reason, _step| {
+            let e = Canvas::<Float>::from_ppm(world.read_from_env_text(text_name).unwrap().as_bytes()).unwrap_err();
+            match reason.as_str() {
+                "a bad magic number" => assert!(matches!(e, PpmError::BadMagic(_)), "{}", e),
+                "truncated data" => assert!(matches!(e, PpmError::Truncated), "{}", e),
+                _ => assert!(matches!(e, PpmError::OutOfRange { .. }), "{}", e),
+            }
+        };
+
+        then regex r"^pixel_at\((\w+), (\d+), (\d+)\) is out of bounds$" (String, usize, usize) |world, variable_name, x, y, _step| {
+            let c = world.read_from_env_canvas(variable_name).unwrap();
+            let (width, height) = (c.width(), c.height());
+            assert_eq!(Err(CanvasError::OutOfBounds { x, y, width, height }), c.pixel_at(x, y));
+        };
+
+        then regex r"^write_pixel\((\w+), (\d+), (\d+), (\w+)\) is out of bounds$" (String, usize, usize, String) |world, variable_name, x, y, color_name, _step| {
+            let color = *world.read_from_env_color(color_name).unwrap();
+            let c = world.read_from_env_canvas_mut(variable_name).unwrap();
+            assert!(matches!(c.write_pixel(x, y, color), Err(CanvasError::OutOfBounds { .. })));
+        };
+
+        // Lines are numbered from 1, the docstring is compared line by line without indentation
+        then regex r"^lines (\d+)-(\d+) of (\w+) are$" (usize, usize, String) |world, first, last, variable_name, step| {
+            let text = world.read_from_env_text(variable_name).unwrap();
+            let actual: Vec<&str> = text.lines().skip(first - 1).take(last + 1 - first).collect();
+            let expected: Vec<&str> = step.docstring().unwrap().lines().map(|l| l.trim()).collect();
+            assert_eq!(expected, actual);
+        };
+
+        then regex r"^no line of (\w+) is longer than (\d+) characters$" (String, usize) |world, variable_name, width, _step| {
+            let text = world.read_from_env_text(variable_name).unwrap();
+            assert!(text.lines().all(|l| l.len() <= width));
+        };
+
+        then regex r"^(\w+) ends with a newline character$" (String) |world, variable_name, _step| {
+            assert!(world.read_from_env_text(variable_name).unwrap().ends_with('\n'));
+        };
+    });
+}
+
+// Scenes rendered by name and checked against images under `features/golden`.
+// Set RAY_TRACER_BLESS=1 to write the current renders as the new golden images.
+mod golden_steps {
+    use ray_tracer::canvas::Canvas;
+    use ray_tracer::color::Color;
+    use ray_tracer::compare::compare;
+    use ray_tracer::image::ImageFormat;
+    use ray_tracer::ray::Tuple;
+    use ray_tracer::transform;
+    use ray_tracer::Float;
+    use cucumber::steps;
+    use std::path::{Path, PathBuf};
+
+    pub const BLESS_VAR: &str = "RAY_TRACER_BLESS";
+
+    fn render_scene(name: &str, width: usize, height: usize) -> Canvas {
+        match name {
+            "gradient" => {
+                let mut c = Canvas::new(width, height);
+                let (w, h) = ((width.max(2) - 1) as Float, (height.max(2) - 1) as Float);
+                for (x, y, p) in c.pixels_mut() {
+                    *p = Color::new(x as Float / w, y as Float / h, 0.5);
+                }
+                c
+            }
+            // The twelve hours of a clock seen from above, as in the book's transformations chapter
+            "clock" => {
+                let mut c = Canvas::new(width, height);
+                let radius = 3.0 / 8.0 * width.min(height) as Float;
+                let twelve = Tuple::point3(0.0, 0.0, 1.0);
+                for hour in 0..12 {
+                    let angle = hour as Float * std::f64::consts::PI as Float / 6.0;
+                    let p = &transform::rotation_y(angle) * twelve;
+                    let x = (width as Float / 2.0 + p.x() * radius).round() as usize;
+                    let y = (height as Float / 2.0 - p.z() * radius).round() as usize;
+                    c.write_pixel(x, y, Color::white()).unwrap();
+                }
+                c
+            }
+            _ => panic!("unknown scene {:?}", name),
+        }
+    }
+
+    fn golden_path(relative: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("features").join(relative)
+    }
+
+    fn failure_path(golden: &Path, suffix: &str) -> PathBuf {
+        let stem = golden.file_stem().unwrap().to_string_lossy();
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden-failures").join(format!("{}.{}.ppm", stem, suffix))
+    }
+
+    // Format of the golden file's extension, Netpbm goldens staying plain text so that they diff in reviews
+    fn golden_format(golden: &Path) -> ImageFormat {
+        match ImageFormat::from_path(golden) {
+            Some(ImageFormat::Ppm(_)) => ImageFormat::PlainPpm,
+            Some(format) => format,
+            None => panic!("{}: unknown image format", golden.display()),
+        }
+    }
+
+    fn bless(actual: &Canvas, golden: &Path) {
+        std::fs::create_dir_all(golden.parent().unwrap()).unwrap();
+        actual.save(golden, golden_format(golden)).unwrap();
+    }
+
+    // Leaves the actual render and the diff in target/golden-failures when they differ
+    fn check_golden(actual: &Canvas, golden: &Path, tolerance: Float) -> Result<(), String> {
+        let expected = Canvas::load(golden).unwrap_or_else(|e| panic!("{}: {}, run with {}=1 to create it", golden.display(), e, BLESS_VAR));
+        let result = compare(&expected, actual, tolerance).unwrap();
+        if result.matches() {
+            return Ok(());
+        }
+        let (actual_path, diff_path) = (failure_path(golden, "actual"), failure_path(golden, "diff"));
+        std::fs::create_dir_all(actual_path.parent().unwrap()).unwrap();
+        actual.save(&actual_path, ImageFormat::from_path(&actual_path).unwrap()).unwrap();
+        result.diff.save(&diff_path, ImageFormat::from_path(&diff_path).unwrap()).unwrap();
+        Err(format!("{} does not match: {}\nactual: {}\ndiff: {}", golden.display(), result, actual_path.display(), diff_path.display()))
+    }
+
+    steps!(crate::MyWorld => {
+        given regex r#"^the scene "(\w+)" rendered at (\d+)x(\d+)$"# (String, usize, usize) |world, name, width, height, _step| {
+            world.add_to_env_canvas(name.clone(), render_scene(&name, width, height));
+            world.rendered_scene = Some(name);
+        };
+
+        when regex r#"^the image is blessed as "([^"]+)"$"# (String) |world, relative, _step| {
+            let name = world.rendered_scene.clone().expect("no scene was rendered");
+            bless(world.read_from_env_canvas(name).unwrap(), &golden_path(&relative));
+        };
+
+        then regex r#"^the image matches "([^"]+)" within (\d+\.\d+)$"# (String, Float) |world, relative, tolerance, _step| {
+            let name = world.rendered_scene.clone().expect("no scene was rendered");
+            let actual = world.read_from_env_canvas(name).unwrap();
+            let golden = golden_path(&relative);
+            if std::env::var_os(BLESS_VAR).is_some_and(|v| v != "0") {
+                bless(actual, &golden);
+                return;
+            }
+            if let Err(message) = check_golden(actual, &golden, tolerance) {
+                panic!("{}", message);
+            }
+        };
+
+        then regex r#"^the image does not match "([^"]+)" within (\d+\.\d+)$"# (String, Float) |world, relative, tolerance, _step| {
+            let name = world.rendered_scene.clone().expect("no scene was rendered");
+            let golden = golden_path(&relative);
+            for suffix in ["actual", "diff"] {
+                let _ = std::fs::remove_file(failure_path(&golden, suffix));
+            }
+            assert!(check_golden(world.read_from_env_canvas(name).unwrap(), &golden, tolerance).is_err());
+        };
+
+        then regex r#"^the actual and diff images of "([^"]+)" are written$"# (String) |world, relative, _step| {
+            let name = world.rendered_scene.clone().expect("no scene was rendered");
+            let actual = world.read_from_env_canvas(name).unwrap();
+            let golden = golden_path(&relative);
+            let written = Canvas::<Float>::load(failure_path(&golden, "actual")).unwrap();
+            assert!(compare(actual, &written, 0.01).unwrap().matches());
+            let diff = Canvas::<Float>::load(failure_path(&golden, "diff")).unwrap();
+            assert_eq!((diff.width(), diff.height()), (actual.width(), actual.height()));
+        };
+
+        then regex r#"^the file "([^"]+)" starts with "([^"]+)"$"# (String, String) |_world, relative, magic, _step| {
+            let data = std::fs::read(golden_path(&relative)).unwrap();
+            assert!(data.starts_with(magic.as_bytes()), "{:?}", String::from_utf8_lossy(&data[..data.len().min(8)]));
+        };
+    });
+}
+
 // Declares a before handler function named `a_before_fn`
 before!(a_before_fn => |_scenario| {
 
@@ -332,7 +944,11 @@ cucumber! {
     features: "./features", // Path to our feature files
     world: crate::MyWorld, // The world needs to be the same for steps and the main cucumber call
     steps: &[
-        example_steps::steps // the `steps!` macro creates a `steps` function in a module
+        example_steps::steps, // the `steps!` macro creates a `steps` function in a module
+        matrix_steps::steps,
+        color_steps::steps,
+        canvas_steps::steps,
+        golden_steps::steps
     ],
     setup: setup, // Optional; called once before everything
     before: &[